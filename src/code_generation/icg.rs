@@ -1,8 +1,18 @@
+use std::fmt;
+
 use trees::Node;
 
 use crate::{
-    symbol_table::{DataType, Symbol, SymbolTable},
-    syntax_analysis::{self, BinaryOperation, Relation, SyntaxComponent, Type, UnaryOperation},
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    span::Span,
+    symbol_table::{
+        coerce_constant, DataType, EnumDefinition, FunctionSignature, LoopLabels, StructDefinition,
+        Symbol, SymbolTable,
+    },
+    syntax_analysis::{
+        self, BinaryOperation, LogicalOperation, Relation, SyntaxComponent, SyntaxNode, Type,
+        UnaryOperation,
+    },
 };
 
 type Label = String;
@@ -13,7 +23,22 @@ enum Instruction {
     Subtraction,
     Multiplication,
     Division,
+    IntegerDivision,
+    Modulo,
+    Power,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseNot,
+    ShiftLeft,
+    ShiftRight,
     Negation,
+    Minus,
+    IntegerToFloat,
+    FloatToInteger,
+    CharacterToInteger,
+    IntegerToCharacter,
+    Concatenation,
     Copy,
     GreaterThan,
     GreaterThanOrEqual,
@@ -23,9 +48,20 @@ enum Instruction {
     NotEqual,
 }
 
+/// Constants keep their type, so integer arithmetic stays exact
+#[derive(Clone, Debug)]
+pub enum Value {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Character(char),
+}
+
 #[derive(Clone, Debug)]
 pub enum Address {
-    Constant(f32),
+    Constant(Value),
+    // entry of the string constant pool
+    String(u32),
     Temp(u32),
 }
 
@@ -42,238 +78,970 @@ pub enum Code {
     ThreeAddress(ThreeAddressCode),
     Label(Label),
     JumpIfFalse(Address, Label),
+    JumpIfTrue(Address, Label),
     Jump(Label),
+    // argument of the next call
+    Param(Address),
+    // calls a function with the given number of parameters, optionally storing its result
+    Call(Label, usize, Option<Address>),
+    // binds the argument at the given position to an address, at the entry of a function
+    Arg(usize, Address),
+    Return(Option<Address>),
+    // result = array[index]
+    IndexedLoad(Address, Address, Address),
+    // array[index] = value
+    IndexedStore(Address, Address, Address),
+    // stops the program unless 0 <= index < length
+    BoundsCheck(Address, usize),
+    JumpIfEqual(Address, Address, Label),
+    // jumps to the label at the given position, which must be within the table
+    JumpTable(Address, Vec<Label>),
 }
 
-impl Code {
-    fn try_get_result_address(&self) -> Result<Address, String> {
-        if let Code::ThreeAddress(three_address_code) = self {
-            Ok(three_address_code.result.clone())
-        } else {
-            Err("Cannot extract result from non-three-address code".into())
+impl Instruction {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Instruction::Addition => "+",
+            Instruction::Subtraction => "-",
+            Instruction::Multiplication => "*",
+            Instruction::Division => "/",
+            Instruction::IntegerDivision => "\\",
+            Instruction::Modulo => "%",
+            Instruction::Power => "**",
+            Instruction::BitwiseAnd => "&",
+            Instruction::BitwiseOr => "|",
+            Instruction::BitwiseXor => "^",
+            Instruction::BitwiseNot => "~",
+            Instruction::ShiftLeft => "<<",
+            Instruction::ShiftRight => ">>",
+            Instruction::Negation => "!",
+            Instruction::Minus => "-",
+            Instruction::IntegerToFloat => "(num) ",
+            Instruction::FloatToInteger | Instruction::CharacterToInteger => "(int) ",
+            Instruction::IntegerToCharacter => "(char) ",
+            Instruction::Concatenation => "concat",
+            Instruction::Copy => "",
+            Instruction::GreaterThan => ">",
+            Instruction::GreaterThanOrEqual => ">=",
+            Instruction::LessThan => "<",
+            Instruction::LessThanOrEqual => "<=",
+            Instruction::Equal => "==",
+            Instruction::NotEqual => "!=",
         }
     }
 }
 
-fn extract_value_address(
-    value_ast: &Node<SyntaxComponent>,
-    code: &mut Vec<Code>,
-    symbol_table: &mut SymbolTable,
-) -> Result<Address, String> {
-    let address = match value_ast.data() {
-        SyntaxComponent::Constant(const_value) => match const_value {
-            syntax_analysis::Constant::Float(float) => {
-                log::trace!("extracting address for a float");
-                Address::Constant(float.clone())
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Integer(integer) => write!(f, "{integer}"),
+            // floats always show a fractional part to tell them apart from integers
+            Value::Float(float) => write!(f, "{float:?}"),
+            Value::Boolean(boolean) => write!(f, "{boolean}"),
+            Value::Character(character) => write!(f, "{character:?}"),
+        }
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Address::Constant(value) => write!(f, "{value}"),
+            Address::String(id) => write!(f, "str_{id}"),
+            Address::Temp(id) => write!(f, "t{id}"),
+        }
+    }
+}
+
+impl fmt::Display for ThreeAddressCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.operand_2 {
+            Some(operand_2) => write!(
+                f,
+                "{} = {} {} {}",
+                self.result,
+                self.operand_1,
+                self.instruction.symbol(),
+                operand_2
+            ),
+            None => write!(
+                f,
+                "{} = {}{}",
+                self.result,
+                self.instruction.symbol(),
+                self.operand_1
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Code::ThreeAddress(three_address_code) => write!(f, "    {three_address_code}"),
+            Code::Label(label) => write!(f, "{label}:"),
+            Code::JumpIfFalse(condition, label) => {
+                write!(f, "    ifFalse {condition} goto {label}")
             }
-            syntax_analysis::Constant::Boolean(boolean) => {
-                log::trace!("extracting address for a boolean");
-                if *boolean {
-                    Address::Constant(1.0)
-                } else {
-                    Address::Constant(0.0)
-                }
+            Code::JumpIfTrue(condition, label) => write!(f, "    if {condition} goto {label}"),
+            Code::Jump(label) => write!(f, "    goto {label}"),
+            Code::Param(address) => write!(f, "    param {address}"),
+            Code::Call(function, parameter_count, Some(result)) => {
+                write!(f, "    {result} = call {function}, {parameter_count}")
+            }
+            Code::Call(function, parameter_count, None) => {
+                write!(f, "    call {function}, {parameter_count}")
+            }
+            Code::Arg(position, address) => write!(f, "    {address} = arg {position}"),
+            Code::Return(Some(address)) => write!(f, "    return {address}"),
+            Code::Return(None) => write!(f, "    return"),
+            Code::IndexedLoad(result, array, index) => {
+                write!(f, "    {result} = {array}[{index}]")
+            }
+            Code::IndexedStore(array, index, value) => {
+                write!(f, "    {array}[{index}] = {value}")
+            }
+            Code::BoundsCheck(index, length) => write!(f, "    checkBounds {index}, {length}"),
+            Code::JumpIfEqual(left, right, label) => {
+                write!(f, "    if {left} == {right} goto {label}")
+            }
+            Code::JumpTable(index, labels) => {
+                write!(f, "    jumpTable {index}, [{}]", labels.join(", "))
             }
-        },
-        SyntaxComponent::Identifier(id_name) => {
-            log::trace!("extracting address for an identifier");
-            let symbol = symbol_table.get(id_name).unwrap();
-            symbol.location.clone().unwrap()
         }
-        SyntaxComponent::BinaryOperation(binary_operation) => match binary_operation {
-            BinaryOperation::Add => {
-                log::trace!("extracting address for an addition");
-                let mut value_children = value_ast.iter();
-                let left_operand = value_children.next().unwrap();
-                let right_operand = value_children.next().unwrap();
-                let result_address = Address::Temp(symbol_table.new_temp());
-
-                let left_operand_address = extract_value_address(left_operand, code, symbol_table)?;
+    }
+}
 
-                let right_operand_address =
-                    extract_value_address(right_operand, code, symbol_table)?;
+fn code_generation_error(code: ErrorCode, span: Span, message: impl Into<String>) -> Diagnostic {
+    Diagnostic::error(Phase::CodeGeneration, code, message).with_span(span)
+}
 
-                code.push(Code::ThreeAddress(ThreeAddressCode {
-                    instruction: Instruction::Addition,
-                    operand_1: left_operand_address,
-                    operand_2: Some(right_operand_address),
-                    result: result_address.clone(),
-                }));
+fn binary_instruction(binary_operation: &BinaryOperation) -> Instruction {
+    match binary_operation {
+        BinaryOperation::Add | BinaryOperation::IncreaseBy => Instruction::Addition,
+        BinaryOperation::Subtract | BinaryOperation::DecreaseBy => Instruction::Subtraction,
+        BinaryOperation::Multiply | BinaryOperation::MultiplyBy => Instruction::Multiplication,
+        BinaryOperation::Divide | BinaryOperation::DivideBy => Instruction::Division,
+        BinaryOperation::IntegerDivide | BinaryOperation::IntegerDivideBy => {
+            Instruction::IntegerDivision
+        }
+        BinaryOperation::Modulo | BinaryOperation::ModuloBy => Instruction::Modulo,
+        BinaryOperation::Power | BinaryOperation::PowerBy => Instruction::Power,
+        BinaryOperation::BitwiseAnd | BinaryOperation::BitwiseAndBy => Instruction::BitwiseAnd,
+        BinaryOperation::BitwiseOr | BinaryOperation::BitwiseOrBy => Instruction::BitwiseOr,
+        BinaryOperation::BitwiseXor | BinaryOperation::BitwiseXorBy => Instruction::BitwiseXor,
+        BinaryOperation::ShiftLeft | BinaryOperation::ShiftLeftBy => Instruction::ShiftLeft,
+        BinaryOperation::ShiftRight | BinaryOperation::ShiftRightBy => Instruction::ShiftRight,
+    }
+}
 
-                result_address
+/**
+ * Type of the value of an expression, which the semantic analysis already
+ * checked. Names it did not resolve are a bug of the compiler, not of the
+ * program, so they panic.
+ */
+fn value_type(value_ast: &Node<SyntaxNode>, symbol_table: &SymbolTable) -> DataType {
+    let mut operand_types = value_ast
+        .iter()
+        .map(|operand| value_type(operand, symbol_table));
+
+    match &value_ast.data().component {
+        SyntaxComponent::Constant(constant) => match constant {
+            syntax_analysis::Constant::Float(_) => DataType::Number,
+            syntax_analysis::Constant::Integer(_) => DataType::Integer,
+            syntax_analysis::Constant::Boolean(_) => DataType::Boolean,
+            syntax_analysis::Constant::String(_) => DataType::String,
+            syntax_analysis::Constant::Character(_) => DataType::Character,
+        },
+        SyntaxComponent::Identifier(id_name) => {
+            symbol_table
+                .get(id_name)
+                .expect("identifiers are resolved by the semantic analysis")
+                .data_type
+        }
+        SyntaxComponent::Conversion(target_type) => DataType::from(target_type),
+        SyntaxComponent::Read => DataType::Number,
+        SyntaxComponent::Call(name) => symbol_table
+            .get_function(name)
+            .and_then(|signature| signature.return_type)
+            .expect("calls used as values are resolved by the semantic analysis"),
+        // an int known at compile time next to a num is a num as well
+        SyntaxComponent::BinaryOperation(_) => {
+            let left_type = operand_types.next().unwrap();
+            if operand_types.any(|right_type| right_type == DataType::Number) {
+                DataType::Number
+            } else {
+                left_type
             }
-            BinaryOperation::IncreaseBy => {
-                log::trace!("extracting address for an increment operation");
-                let mut value_children = value_ast.iter();
-                let target = value_children.next().unwrap();
+        }
+        // an int side next to a num is a num as well
+        SyntaxComponent::Conditional => {
+            let true_value_type = operand_types.nth(1).unwrap();
+            let false_value_type = operand_types.next().unwrap();
+            if false_value_type == DataType::Number {
+                DataType::Number
+            } else {
+                true_value_type
+            }
+        }
+        SyntaxComponent::UnaryOperation(UnaryOperation::Negation) => DataType::Boolean,
+        SyntaxComponent::UnaryOperation(_) => operand_types.next().unwrap(),
+        // identifiers of arrays have the type of their elements
+        SyntaxComponent::Index => operand_types.next().unwrap(),
+        SyntaxComponent::Member(field_name) => match symbol_table.referenced_enum(value_ast) {
+            Some(enum_name) => DataType::Enum(enum_name),
+            None => match operand_types.next().unwrap() {
+                DataType::Struct(struct_name) => symbol_table
+                    .types()
+                    .get_struct(&struct_name)
+                    .and_then(|definition| definition.field(field_name))
+                    .expect("fields are resolved by the semantic analysis")
+                    .data_type
+                    .clone(),
+                _ => unreachable!("only structs have fields"),
+            },
+        },
+        // relations and logical operations
+        _ => DataType::Boolean,
+    }
+}
 
-                if !target.data().is_identifier() {
-                    return Err("Cannot increment non-identifier".into());
-                }
+/**
+ * Extracts the address of a value stored where a value of `target_type` is
+ * expected. Ints known at compile time used as nums become float constants,
+ * and conditional expressions picking them are nums as well
+ */
+fn extract_converted_address(
+    value: &Node<SyntaxNode>,
+    target_type: &DataType,
+    code: &mut Vec<Code>,
+    symbol_table: &mut SymbolTable,
+) -> Result<Address, Diagnostic> {
+    if *target_type != DataType::Number {
+        return extract_value_address(value, code, symbol_table);
+    }
 
-                let target_address = extract_value_address(target, code, symbol_table)?;
+    match symbol_table.evaluate_constant(value) {
+        Ok(Value::Integer(integer)) => Ok(Address::Constant(Value::Float(integer as f64))),
+        _ if value.data().component == SyntaxComponent::Conditional => {
+            generate_conditional(value, true, code, symbol_table)
+        }
+        _ => extract_value_address(value, code, symbol_table),
+    }
+}
 
-                let increase_by_value_tree = value_children.next().unwrap();
+/**
+ * Generates a conditional expression, both sides storing their value in the
+ * same temporary. Sides are converted to nums when `is_number` is set.
+ *
+ * Example, for `c ? a : 1`:
+ *     ifFalse c goto conditional_else_1
+ *     t1 = a
+ *     goto conditional_after_1
+ * conditional_else_1:
+ *     t1 = 1
+ * conditional_after_1:
+ */
+fn generate_conditional(
+    conditional: &Node<SyntaxNode>,
+    is_number: bool,
+    code: &mut Vec<Code>,
+    symbol_table: &mut SymbolTable,
+) -> Result<Address, Diagnostic> {
+    let conditional_id = symbol_table.new_conditional();
+    let else_label = format!("conditional_else_{}", conditional_id);
+    let after_label = format!("conditional_after_{}", conditional_id);
+    let result_address = Address::Temp(symbol_table.new_temp());
+    let side_type = if is_number {
+        DataType::Number
+    } else {
+        value_type(conditional, symbol_table)
+    };
 
-                let increase_by_value_address =
-                    extract_value_address(increase_by_value_tree, code, symbol_table)?;
+    let mut children = conditional.iter();
+    let condition = children.next().unwrap();
+    let true_value = children.next().unwrap();
+    let false_value = children.next().unwrap();
+
+    let condition_address = extract_value_address(condition, code, symbol_table)?;
+    code.push(Code::JumpIfFalse(condition_address, else_label.clone()));
+
+    let true_value_address = extract_converted_address(true_value, &side_type, code, symbol_table)?;
+    code.push(Code::ThreeAddress(ThreeAddressCode {
+        instruction: Instruction::Copy,
+        operand_1: true_value_address,
+        operand_2: None,
+        result: result_address.clone(),
+    }));
+    code.push(Code::Jump(after_label.clone()));
+
+    code.push(Code::Label(else_label));
+    let false_value_address =
+        extract_converted_address(false_value, &side_type, code, symbol_table)?;
+    code.push(Code::ThreeAddress(ThreeAddressCode {
+        instruction: Instruction::Copy,
+        operand_1: false_value_address,
+        operand_2: None,
+        result: result_address.clone(),
+    }));
+    code.push(Code::Label(after_label));
+
+    Ok(result_address)
+}
 
-                code.push(Code::ThreeAddress(ThreeAddressCode {
-                    instruction: Instruction::Addition,
-                    operand_1: target_address.clone(),
-                    operand_2: Some(increase_by_value_address),
-                    result: target_address.clone(),
-                }));
+/// Extracts the addresses of both operands, converting ints known at compile time when the other side is a `num`
+fn extract_operand_addresses(
+    left_operand: &Node<SyntaxNode>,
+    right_operand: &Node<SyntaxNode>,
+    code: &mut Vec<Code>,
+    symbol_table: &mut SymbolTable,
+) -> Result<(Address, Address), Diagnostic> {
+    let left_operand_type = value_type(left_operand, symbol_table);
+    let operand_type = if value_type(right_operand, symbol_table) == DataType::Number {
+        DataType::Number
+    } else {
+        left_operand_type
+    };
 
-                target_address
-            }
-            BinaryOperation::Subtract => {
-                log::trace!("extracting address for a subtraction operation");
-                let mut value_children = value_ast.iter();
-                let left_operand = value_children.next().unwrap();
-                let right_operand = value_children.next().unwrap();
-                let result_address = Address::Temp(symbol_table.new_temp());
+    let left_operand_address =
+        extract_converted_address(left_operand, &operand_type, code, symbol_table)?;
+    let right_operand_address =
+        extract_converted_address(right_operand, &operand_type, code, symbol_table)?;
+
+    Ok((left_operand_address, right_operand_address))
+}
 
-                let left_operand_address = extract_value_address(left_operand, code, symbol_table)?;
+/// Runtime function printing a value of the given type
+fn print_builtin(data_type: DataType) -> Label {
+    let name = match data_type {
+        DataType::Integer => "print_int",
+        DataType::Number => "print_num",
+        DataType::Boolean => "print_bool",
+        DataType::String => "print_str",
+        DataType::Character => "print_char",
+        DataType::Struct(_) => unreachable!("structs cannot be printed"),
+        DataType::Enum(_) => unreachable!("enums cannot be printed"),
+    };
 
-                let right_operand_address =
-                    extract_value_address(right_operand, code, symbol_table)?;
+    name.to_string()
+}
 
-                code.push(Code::ThreeAddress(ThreeAddressCode {
-                    instruction: Instruction::Subtraction,
-                    operand_1: left_operand_address,
-                    operand_2: Some(right_operand_address),
-                    result: result_address.clone(),
-                }));
+fn function_label(name: &str) -> Label {
+    format!("fn_{name}")
+}
 
-                result_address
+/// Adds the functions defined in a sequence to the table, so they can be called before their definition
+fn register_functions(sequence: &Node<SyntaxNode>, symbol_table: &mut SymbolTable) {
+    for statement in sequence.iter() {
+        if let SyntaxComponent::Function(name) = &statement.data().component {
+            symbol_table
+                .insert_function(name.clone(), FunctionSignature::from_definition(statement));
+        }
+    }
+}
+
+/// Adds the structs and enums defined in a sequence to the type registry
+fn register_types(sequence: &Node<SyntaxNode>, symbol_table: &mut SymbolTable) {
+    for statement in sequence.iter() {
+        match &statement.data().component {
+            SyntaxComponent::Struct(name) => {
+                let definition = StructDefinition::from_definition(statement, symbol_table.types());
+                symbol_table
+                    .types_mut()
+                    .insert_struct(name.clone(), definition);
             }
-            BinaryOperation::DecreaseBy => {
-                log::trace!("extracting address for a decrement operation");
-                let mut value_children = value_ast.iter();
-                let target = value_children.next().unwrap();
+            SyntaxComponent::Enum(name) => {
+                let definition = EnumDefinition::from_definition(statement);
+                symbol_table
+                    .types_mut()
+                    .insert_enum(name.clone(), definition);
+            }
+            _ => {}
+        }
+    }
+}
 
-                if !target.data().is_identifier() {
-                    return Err("Cannot decrement non-identifier".into());
-                }
+// switches with fewer labels compare the value to each of them
+const JUMP_TABLE_MIN_LABELS: usize = 4;
+
+/**
+ * Whether a switch jumps through a table instead of comparing its value to
+ * every label, which pays off for many labels covering at least half of the
+ * values between the smallest and the largest one.
+ */
+fn uses_jump_table(values: &[i64]) -> bool {
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+        return false;
+    };
+    let range = max as i128 - min as i128 + 1;
 
-                let target_address = extract_value_address(target, code, symbol_table)?;
+    values.len() >= JUMP_TABLE_MIN_LABELS && values.len() as i128 * 2 >= range
+}
 
-                let increase_by_value_tree = value_children.next().unwrap();
+/**
+ * Jumps to the case matching a switched value, or to `default_label` when no
+ * case does. `targets` pairs the value of every case label with its case.
+ *
+ * Example, as a jump table for labels 1 to 4 without 3:
+ *     t2 = t1 - 1
+ *     t3 = t2 < 0
+ *     if t3 goto switch_after_1
+ *     t4 = t2 > 3
+ *     if t4 goto switch_after_1
+ *     jumpTable t2, [switch_case_1_0, switch_case_1_1, switch_after_1, switch_case_1_2]
+ *
+ * Example, as a chain of comparisons:
+ *     if t1 == 1 goto switch_case_1_0
+ *     if t1 == 5 goto switch_case_1_1
+ *     goto switch_after_1
+ */
+fn switch_dispatch(
+    value_address: Address,
+    is_character: bool,
+    targets: &[(i64, Label)],
+    default_label: &Label,
+    code: &mut Vec<Code>,
+    symbol_table: &mut SymbolTable,
+) {
+    let values: Vec<i64> = targets.iter().map(|(value, _)| *value).collect();
+
+    if !uses_jump_table(&values) {
+        for (value, label) in targets {
+            // characters are compared to characters
+            let constant = match u32::try_from(*value).ok().and_then(char::from_u32) {
+                Some(character) if is_character => Value::Character(character),
+                _ => Value::Integer(*value),
+            };
+            code.push(Code::JumpIfEqual(
+                value_address.clone(),
+                Address::Constant(constant),
+                label.clone(),
+            ));
+        }
+        code.push(Code::Jump(default_label.clone()));
+        return;
+    }
 
-                let increase_by_value_address =
-                    extract_value_address(increase_by_value_tree, code, symbol_table)?;
+    let min = *values.iter().min().unwrap();
+    let max = *values.iter().max().unwrap();
+
+    // the table is indexed by the int value minus the smallest label
+    let mut index_address = value_address;
+    if is_character {
+        let code_address = Address::Temp(symbol_table.new_temp());
+        code.push(Code::ThreeAddress(ThreeAddressCode {
+            instruction: Instruction::CharacterToInteger,
+            operand_1: index_address,
+            operand_2: None,
+            result: code_address.clone(),
+        }));
+        index_address = code_address;
+    }
+    if min != 0 {
+        let offset_address = Address::Temp(symbol_table.new_temp());
+        code.push(Code::ThreeAddress(ThreeAddressCode {
+            instruction: Instruction::Subtraction,
+            operand_1: index_address,
+            operand_2: Some(Address::Constant(Value::Integer(min))),
+            result: offset_address.clone(),
+        }));
+        index_address = offset_address;
+    }
 
-                code.push(Code::ThreeAddress(ThreeAddressCode {
-                    instruction: Instruction::Addition,
-                    operand_1: target_address.clone(),
-                    operand_2: Some(increase_by_value_address),
-                    result: target_address.clone(),
-                }));
+    for (instruction, bound) in [
+        (Instruction::LessThan, 0),
+        (Instruction::GreaterThan, max - min),
+    ] {
+        let out_of_range_address = Address::Temp(symbol_table.new_temp());
+        code.push(Code::ThreeAddress(ThreeAddressCode {
+            instruction,
+            operand_1: index_address.clone(),
+            operand_2: Some(Address::Constant(Value::Integer(bound))),
+            result: out_of_range_address.clone(),
+        }));
+        code.push(Code::JumpIfTrue(
+            out_of_range_address,
+            default_label.clone(),
+        ));
+    }
 
-                target_address
-            }
-            BinaryOperation::Multiply => {
-                log::trace!("extracting address for a multiplication operation");
-                let mut value_children = value_ast.iter();
-                let left_operand = value_children.next().unwrap();
-                let right_operand = value_children.next().unwrap();
-                let result_address = Address::Temp(symbol_table.new_temp());
+    // values between the labels go to the default case
+    let table = (min..=max)
+        .map(|value| {
+            targets
+                .iter()
+                .find(|(target_value, _)| *target_value == value)
+                .map_or(default_label.clone(), |(_, label)| label.clone())
+        })
+        .collect();
+    code.push(Code::JumpTable(index_address, table));
+}
 
-                let left_operand_address = extract_value_address(left_operand, code, symbol_table)?;
+/**
+ * Passes the arguments of a call and calls the function.
+ *
+ * Example, for `max(a, 2)`:
+ *     param t1
+ *     param 2
+ *     t3 = call fn_max, 2
+ *
+ * Returns where the result is stored, if the function has one
+ */
+fn generate_call(
+    call_ast: &Node<SyntaxNode>,
+    code: &mut Vec<Code>,
+    symbol_table: &mut SymbolTable,
+) -> Result<Option<Address>, Diagnostic> {
+    let SyntaxComponent::Call(name) = &call_ast.data().component else {
+        unreachable!("only calls are generated as calls");
+    };
+    let signature = symbol_table
+        .get_function(name)
+        .ok_or(code_generation_error(
+            ErrorCode::UndeclaredIdentifier,
+            call_ast.data().span,
+            format!("Undeclared function: {}", name),
+        ))?;
+
+    // every argument is evaluated before the first one is passed
+    let mut argument_addresses = vec![];
+    for (argument, parameter_type) in call_ast.iter().zip(&signature.parameters) {
+        argument_addresses.push(extract_converted_address(
+            argument,
+            parameter_type,
+            code,
+            symbol_table,
+        )?);
+    }
 
-                let right_operand_address =
-                    extract_value_address(right_operand, code, symbol_table)?;
+    let argument_count = argument_addresses.len();
+    for argument_address in argument_addresses {
+        code.push(Code::Param(argument_address));
+    }
 
-                code.push(Code::ThreeAddress(ThreeAddressCode {
-                    instruction: Instruction::Multiplication,
-                    operand_1: left_operand_address,
-                    operand_2: Some(right_operand_address),
-                    result: result_address.clone(),
-                }));
+    let result_address = signature
+        .return_type
+        .map(|_| Address::Temp(symbol_table.new_temp()));
+    code.push(Code::Call(
+        function_label(name),
+        argument_count,
+        result_address.clone(),
+    ));
 
-                result_address
-            }
-            BinaryOperation::MultiplyBy => {
-                log::trace!("extracting address for a multiplication operation");
-                let mut value_children = value_ast.iter();
-                let target = value_children.next().unwrap();
+    Ok(result_address)
+}
 
-                if !target.data().is_identifier() {
-                    return Err("Cannot decrement non-identifier".into());
-                }
+/**
+ * Extracts the address of a struct value and the slot it starts at. Fields
+ * holding a struct start at their offset within the enclosing struct.
+ */
+fn extract_struct_address(
+    struct_ast: &Node<SyntaxNode>,
+    symbol_table: &SymbolTable,
+) -> Result<(Address, usize), Diagnostic> {
+    let span = struct_ast.data().span;
+    match &struct_ast.data().component {
+        SyntaxComponent::Identifier(name) => {
+            let symbol = symbol_table.get(name).unwrap();
+            Ok((symbol.location.unwrap(), 0))
+        }
+        SyntaxComponent::Member(field_name) => {
+            let value = struct_ast.iter().next().unwrap();
+            let (address, offset) = extract_struct_address(value, symbol_table)?;
+
+            let DataType::Struct(struct_name) = value_type(value, symbol_table) else {
+                return Err(code_generation_error(
+                    ErrorCode::MalformedSyntaxTree,
+                    span,
+                    "Only structs have fields",
+                ));
+            };
+            let field = symbol_table
+                .types()
+                .get_struct(&struct_name)
+                .and_then(|definition| definition.field(field_name))
+                .ok_or(code_generation_error(
+                    ErrorCode::UndeclaredIdentifier,
+                    span,
+                    format!("Struct `{struct_name}` has no field `{field_name}`"),
+                ))?;
+
+            Ok((address, offset + field.offset))
+        }
+        _ => Err(code_generation_error(
+            ErrorCode::MalformedSyntaxTree,
+            span,
+            "Expected a struct variable or field",
+        )),
+    }
+}
 
-                let target_address = extract_value_address(target, code, symbol_table)?;
+/**
+ * Copies every slot of a struct value into the slots starting at `target`.
+ *
+ * Example, for `p = q` with two fields:
+ *     t3 = t2[0]
+ *     t1[0] = t3
+ *     t4 = t2[1]
+ *     t1[1] = t4
+ */
+fn copy_struct(
+    target: (Address, usize),
+    value_ast: &Node<SyntaxNode>,
+    code: &mut Vec<Code>,
+    symbol_table: &mut SymbolTable,
+) -> Result<(), Diagnostic> {
+    let (target_address, target_offset) = target;
+    let (value_address, value_offset) = extract_struct_address(value_ast, symbol_table)?;
+    let size = symbol_table
+        .types()
+        .size_of(&value_type(value_ast, symbol_table));
+
+    for slot in 0..size {
+        let slot_address = Address::Temp(symbol_table.new_temp());
+        code.push(Code::IndexedLoad(
+            slot_address.clone(),
+            value_address.clone(),
+            Address::Constant(Value::Integer((value_offset + slot) as i64)),
+        ));
+        code.push(Code::IndexedStore(
+            target_address.clone(),
+            Address::Constant(Value::Integer((target_offset + slot) as i64)),
+            slot_address,
+        ));
+    }
 
-                let increase_by_value_tree = value_children.next().unwrap();
+    Ok(())
+}
 
-                let increase_by_value_address =
-                    extract_value_address(increase_by_value_tree, code, symbol_table)?;
+/**
+ * Extracts the addresses of an array and of the index of an element, or of a
+ * struct and the offset of a field. Indices that are not constant are checked
+ * when the program runs, constant ones were already checked by the semantic
+ * analysis.
+ */
+fn extract_element_addresses(
+    element_ast: &Node<SyntaxNode>,
+    code: &mut Vec<Code>,
+    symbol_table: &mut SymbolTable,
+) -> Result<(Address, Address), Diagnostic> {
+    if let SyntaxComponent::Member(_) = element_ast.data().component {
+        let (struct_address, offset) = extract_struct_address(element_ast, symbol_table)?;
+        return Ok((
+            struct_address,
+            Address::Constant(Value::Integer(offset as i64)),
+        ));
+    }
 
-                code.push(Code::ThreeAddress(ThreeAddressCode {
-                    instruction: Instruction::Multiplication,
-                    operand_1: target_address.clone(),
-                    operand_2: Some(increase_by_value_address),
-                    result: target_address.clone(),
-                }));
+    let mut children = element_ast.iter();
+    let array = children.next().unwrap();
+    let index = children.next().unwrap();
+
+    let array_name = array
+        .data()
+        .component
+        .try_get_identifier_name(Phase::CodeGeneration)
+        .map_err(|diagnostic| diagnostic.with_span(array.data().span))?;
+    let symbol = symbol_table.get(&array_name).unwrap();
+    let array_address = symbol.location.unwrap();
+
+    let index_address = match symbol_table.constant_value(index) {
+        Some(constant_index) => Address::Constant(Value::Integer(constant_index)),
+        None => extract_value_address(index, code, symbol_table)?,
+    };
+    if let (Address::Temp(_), Some(length)) = (&index_address, symbol.length) {
+        code.push(Code::BoundsCheck(index_address.clone(), length));
+    }
 
-                target_address
-            }
-            BinaryOperation::Divide => {
-                log::trace!("extracting address for a division operation");
-                let mut value_children = value_ast.iter();
-                let left_operand = value_children.next().unwrap();
-                let right_operand = value_children.next().unwrap();
-                let result_address = Address::Temp(symbol_table.new_temp());
+    Ok((array_address, index_address))
+}
 
-                let left_operand_address = extract_value_address(left_operand, code, symbol_table)?;
+/**
+ * Applies an instruction to an array element or a field and an operand,
+ * storing the result back into it.
+ *
+ * Example, for `a[i] += 2`:
+ *     checkBounds t2, 10
+ *     t3 = t1[t2]
+ *     t3 = t3 + 2
+ *     t1[t2] = t3
+ */
+fn update_element(
+    element_ast: &Node<SyntaxNode>,
+    instruction: Instruction,
+    operand_address: Address,
+    code: &mut Vec<Code>,
+    symbol_table: &mut SymbolTable,
+) -> Result<Address, Diagnostic> {
+    let (array_address, index_address) =
+        extract_element_addresses(element_ast, code, symbol_table)?;
+    let element_address = Address::Temp(symbol_table.new_temp());
+
+    code.push(Code::IndexedLoad(
+        element_address.clone(),
+        array_address.clone(),
+        index_address.clone(),
+    ));
+    code.push(Code::ThreeAddress(ThreeAddressCode {
+        instruction,
+        operand_1: element_address.clone(),
+        operand_2: Some(operand_address),
+        result: element_address.clone(),
+    }));
+    code.push(Code::IndexedStore(
+        array_address,
+        index_address,
+        element_address.clone(),
+    ));
+
+    Ok(element_address)
+}
 
-                let right_operand_address =
-                    extract_value_address(right_operand, code, symbol_table)?;
+/// Jumps taken when a condition holds and when it does not, their labels are backpatched once known
+struct JumpLists {
+    true_list: Vec<usize>,
+    false_list: Vec<usize>,
+}
 
-                code.push(Code::ThreeAddress(ThreeAddressCode {
-                    instruction: Instruction::Multiplication,
-                    operand_1: left_operand_address,
-                    operand_2: Some(right_operand_address),
-                    result: result_address.clone(),
-                }));
+/// Points the jumps at `positions` in `code` to `label`
+fn backpatch(code: &mut [Code], positions: &[usize], label: &Label) {
+    for &position in positions {
+        match &mut code[position] {
+            Code::Jump(target) | Code::JumpIfFalse(_, target) | Code::JumpIfTrue(_, target) => {
+                *target = label.clone()
+            }
+            _ => unreachable!("only jumps are backpatched"),
+        }
+    }
+}
 
-                result_address
+/**
+ * Generates short-circuit code for a boolean expression, which jumps away as
+ * soon as its value is known instead of computing it.
+ *
+ * Example, for `a && b`:
+ *     if a goto logical_1
+ *     goto <false>
+ * logical_1:
+ *     if b goto <true>
+ *     goto <false>
+ */
+fn jumping_code(
+    condition_ast: &Node<SyntaxNode>,
+    code: &mut Vec<Code>,
+    symbol_table: &mut SymbolTable,
+) -> Result<JumpLists, Diagnostic> {
+    match &condition_ast.data().component {
+        SyntaxComponent::LogicalOperation(logical_operation) => {
+            let mut children = condition_ast.iter();
+            let left_operand = children.next().unwrap();
+            let right_operand = children.next().unwrap();
+
+            let left_jumps = jumping_code(left_operand, code, symbol_table)?;
+
+            // the right operand only runs when the left one does not decide the result
+            let right_label = format!("logical_{}", symbol_table.new_logical());
+            code.push(Code::Label(right_label.clone()));
+
+            match logical_operation {
+                LogicalOperation::And => {
+                    backpatch(code, &left_jumps.true_list, &right_label);
+                    let right_jumps = jumping_code(right_operand, code, symbol_table)?;
+
+                    Ok(JumpLists {
+                        true_list: right_jumps.true_list,
+                        false_list: [left_jumps.false_list, right_jumps.false_list].concat(),
+                    })
+                }
+                LogicalOperation::Or => {
+                    backpatch(code, &left_jumps.false_list, &right_label);
+                    let right_jumps = jumping_code(right_operand, code, symbol_table)?;
+
+                    Ok(JumpLists {
+                        true_list: [left_jumps.true_list, right_jumps.true_list].concat(),
+                        false_list: right_jumps.false_list,
+                    })
+                }
+            }
+        }
+        SyntaxComponent::UnaryOperation(UnaryOperation::Negation) => {
+            let operand = condition_ast.iter().next().unwrap();
+            let operand_jumps = jumping_code(operand, code, symbol_table)?;
+
+            Ok(JumpLists {
+                true_list: operand_jumps.false_list,
+                false_list: operand_jumps.true_list,
+            })
+        }
+        _ => {
+            let condition_address = extract_value_address(condition_ast, code, symbol_table)?;
+            code.push(Code::JumpIfTrue(condition_address, Label::new()));
+            code.push(Code::Jump(Label::new()));
+
+            Ok(JumpLists {
+                true_list: vec![code.len() - 2],
+                false_list: vec![code.len() - 1],
+            })
+        }
+    }
+}
+
+fn extract_value_address(
+    value_ast: &Node<SyntaxNode>,
+    code: &mut Vec<Code>,
+    symbol_table: &mut SymbolTable,
+) -> Result<Address, Diagnostic> {
+    let span = value_ast.data().span;
+    let address = match &value_ast.data().component {
+        SyntaxComponent::Constant(const_value) => match const_value {
+            syntax_analysis::Constant::Integer(integer) => {
+                log::trace!("extracting address for an integer");
+                Address::Constant(Value::Integer(*integer))
+            }
+            syntax_analysis::Constant::Float(float) => {
+                log::trace!("extracting address for a float");
+                Address::Constant(Value::Float(*float))
+            }
+            syntax_analysis::Constant::Boolean(boolean) => {
+                log::trace!("extracting address for a boolean");
+                Address::Constant(Value::Boolean(*boolean))
+            }
+            syntax_analysis::Constant::String(string) => {
+                log::trace!("extracting address for a string");
+                Address::String(symbol_table.intern_string(string))
+            }
+            syntax_analysis::Constant::Character(character) => {
+                log::trace!("extracting address for a character");
+                Address::Constant(Value::Character(*character))
+            }
+        },
+        SyntaxComponent::Identifier(id_name) => {
+            log::trace!("extracting address for an identifier");
+            let symbol = symbol_table.get(id_name).unwrap();
+            symbol.location.clone().unwrap()
+        }
+        SyntaxComponent::BinaryOperation(binary_operation) => {
+            log::trace!("extracting address for a {:?} operation", binary_operation);
+            let mut value_children = value_ast.iter();
+            let left_operand = value_children.next().unwrap();
+            let right_operand = value_children.next().unwrap();
+
+            let instruction = if value_type(left_operand, symbol_table) == DataType::String {
+                Instruction::Concatenation
+            } else {
+                binary_instruction(binary_operation)
+            };
+
+            if binary_operation.is_compound_assignment()
+                && left_operand.data().component.is_element_access()
+            {
+                let right_operand_address = extract_converted_address(
+                    right_operand,
+                    &value_type(left_operand, symbol_table),
+                    code,
+                    symbol_table,
+                )?;
+
+                return update_element(
+                    left_operand,
+                    instruction,
+                    right_operand_address,
+                    code,
+                    symbol_table,
+                );
             }
-            BinaryOperation::DivideBy => {
-                log::trace!("extracting address for a division operation");
-                let mut value_children = value_ast.iter();
-                let target = value_children.next().unwrap();
 
-                if !target.data().is_identifier() {
-                    return Err("Cannot decrement non-identifier".into());
+            let result_address = if binary_operation.is_compound_assignment() {
+                if !left_operand.data().component.is_identifier() {
+                    return Err(code_generation_error(
+                        ErrorCode::InvalidAssignmentTarget,
+                        span,
+                        format!("Cannot apply {:?} to non-identifier", binary_operation),
+                    ));
                 }
 
-                let target_address = extract_value_address(target, code, symbol_table)?;
+                // the result is stored back into the target
+                extract_value_address(left_operand, code, symbol_table)?
+            } else {
+                Address::Temp(symbol_table.new_temp())
+            };
 
-                let increase_by_value_tree = value_children.next().unwrap();
+            let (left_operand_address, right_operand_address) =
+                extract_operand_addresses(left_operand, right_operand, code, symbol_table)?;
 
-                let increase_by_value_address =
-                    extract_value_address(increase_by_value_tree, code, symbol_table)?;
+            code.push(Code::ThreeAddress(ThreeAddressCode {
+                instruction,
+                operand_1: left_operand_address,
+                operand_2: Some(right_operand_address),
+                result: result_address.clone(),
+            }));
 
-                code.push(Code::ThreeAddress(ThreeAddressCode {
-                    instruction: Instruction::Division,
-                    operand_1: target_address.clone(),
-                    operand_2: Some(increase_by_value_address),
-                    result: target_address.clone(),
-                }));
+            result_address
+        }
+        SyntaxComponent::LogicalOperation(_) => {
+            log::trace!("extracting address for a logical operation");
+            let logical_id = symbol_table.new_logical();
+            let true_label = format!("logical_true_{}", logical_id);
+            let false_label = format!("logical_false_{}", logical_id);
+            let after_label = format!("logical_after_{}", logical_id);
+            let result_address = Address::Temp(symbol_table.new_temp());
+
+            let jumps = jumping_code(value_ast, code, symbol_table)?;
+
+            backpatch(code, &jumps.true_list, &true_label);
+            code.push(Code::Label(true_label));
+            code.push(Code::ThreeAddress(ThreeAddressCode {
+                instruction: Instruction::Copy,
+                operand_1: Address::Constant(Value::Boolean(true)),
+                operand_2: None,
+                result: result_address.clone(),
+            }));
+            code.push(Code::Jump(after_label.clone()));
 
-                target_address
-            }
-        },
+            backpatch(code, &jumps.false_list, &false_label);
+            code.push(Code::Label(false_label));
+            code.push(Code::ThreeAddress(ThreeAddressCode {
+                instruction: Instruction::Copy,
+                operand_1: Address::Constant(Value::Boolean(false)),
+                operand_2: None,
+                result: result_address.clone(),
+            }));
+            code.push(Code::Label(after_label));
+
+            result_address
+        }
+        SyntaxComponent::Conditional => {
+            log::trace!("extracting address for a conditional expression");
+            let is_number = value_type(value_ast, symbol_table) == DataType::Number;
+            generate_conditional(value_ast, is_number, code, symbol_table)?
+        }
         SyntaxComponent::UnaryOperation(operation) => match operation {
             UnaryOperation::Increment => {
                 log::trace!("extracting address for a unary increment operation");
                 let mut value_children = value_ast.iter();
                 let target = value_children.next().unwrap();
+                let step = if value_type(target, symbol_table) == DataType::Number {
+                    Value::Float(1.0)
+                } else {
+                    Value::Integer(1)
+                };
+
+                if target.data().component.is_element_access() {
+                    return update_element(
+                        target,
+                        Instruction::Addition,
+                        Address::Constant(step),
+                        code,
+                        symbol_table,
+                    );
+                }
 
-                if !target.data().is_identifier() {
-                    return Err("Cannot increment non-identifier".into());
+                if !target.data().component.is_identifier() {
+                    return Err(code_generation_error(
+                        ErrorCode::InvalidAssignmentTarget,
+                        span,
+                        "Cannot increment non-identifier",
+                    ));
                 }
 
                 let target_address = extract_value_address(target, code, symbol_table)?;
@@ -281,7 +1049,7 @@ fn extract_value_address(
                 code.push(Code::ThreeAddress(ThreeAddressCode {
                     instruction: Instruction::Addition,
                     operand_1: target_address.clone(),
-                    operand_2: Some(Address::Constant(1.0)),
+                    operand_2: Some(Address::Constant(step)),
                     result: target_address.clone(),
                 }));
 
@@ -291,9 +1059,28 @@ fn extract_value_address(
                 log::trace!("extracting address for a unary decrement operation");
                 let mut value_children = value_ast.iter();
                 let target = value_children.next().unwrap();
+                let step = if value_type(target, symbol_table) == DataType::Number {
+                    Value::Float(1.0)
+                } else {
+                    Value::Integer(1)
+                };
+
+                if target.data().component.is_element_access() {
+                    return update_element(
+                        target,
+                        Instruction::Subtraction,
+                        Address::Constant(step),
+                        code,
+                        symbol_table,
+                    );
+                }
 
-                if !target.data().is_identifier() {
-                    return Err("Cannot decrement non-identifier".into());
+                if !target.data().component.is_identifier() {
+                    return Err(code_generation_error(
+                        ErrorCode::InvalidAssignmentTarget,
+                        span,
+                        "Cannot decrement non-identifier",
+                    ));
                 }
 
                 let target_address = extract_value_address(target, code, symbol_table)?;
@@ -301,7 +1088,7 @@ fn extract_value_address(
                 code.push(Code::ThreeAddress(ThreeAddressCode {
                     instruction: Instruction::Subtraction,
                     operand_1: target_address.clone(),
-                    operand_2: Some(Address::Constant(1.0)),
+                    operand_2: Some(Address::Constant(step)),
                     result: target_address.clone(),
                 }));
 
@@ -309,25 +1096,150 @@ fn extract_value_address(
             }
             UnaryOperation::Negation => {
                 log::trace!("extracting address for a unary negation operation");
-                let mut value_children = value_ast.iter();
-                let target = value_children.next().unwrap();
+                let operand = value_ast.iter().next().unwrap();
+                let result_address = Address::Temp(symbol_table.new_temp());
+
+                let operand_address = extract_value_address(operand, code, symbol_table)?;
 
-                if !target.data().is_identifier() {
-                    return Err("Cannot decrement non-identifier".into());
+                code.push(Code::ThreeAddress(ThreeAddressCode {
+                    instruction: Instruction::Negation,
+                    operand_1: operand_address,
+                    operand_2: None,
+                    result: result_address.clone(),
+                }));
+
+                result_address
+            }
+            UnaryOperation::Minus => {
+                log::trace!("extracting address for a unary minus operation");
+                let operand = value_ast.iter().next().unwrap();
+
+                // negative literals are constants themselves
+                match &operand.data().component {
+                    SyntaxComponent::Constant(syntax_analysis::Constant::Integer(integer)) => {
+                        return Ok(Address::Constant(Value::Integer(-integer)));
+                    }
+                    SyntaxComponent::Constant(syntax_analysis::Constant::Float(float)) => {
+                        return Ok(Address::Constant(Value::Float(-float)));
+                    }
+                    _ => {}
                 }
 
-                let target_address = extract_value_address(target, code, symbol_table)?;
+                let result_address = Address::Temp(symbol_table.new_temp());
+
+                let operand_address = extract_value_address(operand, code, symbol_table)?;
 
                 code.push(Code::ThreeAddress(ThreeAddressCode {
-                    instruction: Instruction::Negation,
-                    operand_1: target_address.clone(),
+                    instruction: Instruction::Minus,
+                    operand_1: operand_address,
                     operand_2: None,
-                    result: target_address.clone(),
+                    result: result_address.clone(),
                 }));
 
-                target_address
+                result_address
+            }
+            UnaryOperation::BitwiseNot => {
+                log::trace!("extracting address for a bitwise not operation");
+                let operand = value_ast.iter().next().unwrap();
+                let result_address = Address::Temp(symbol_table.new_temp());
+
+                let operand_address = extract_value_address(operand, code, symbol_table)?;
+
+                code.push(Code::ThreeAddress(ThreeAddressCode {
+                    instruction: Instruction::BitwiseNot,
+                    operand_1: operand_address,
+                    operand_2: None,
+                    result: result_address.clone(),
+                }));
+
+                result_address
             }
         },
+        SyntaxComponent::Member(_) if symbol_table.referenced_enum(value_ast).is_some() => {
+            log::trace!("extracting address for an enum variant");
+            let position = symbol_table.constant_value(value_ast).unwrap();
+            Address::Constant(Value::Integer(position))
+        }
+        SyntaxComponent::Index | SyntaxComponent::Member(_) => {
+            log::trace!("extracting address for an array element or a field");
+            let (array_address, index_address) =
+                extract_element_addresses(value_ast, code, symbol_table)?;
+            let result_address = Address::Temp(symbol_table.new_temp());
+
+            code.push(Code::IndexedLoad(
+                result_address.clone(),
+                array_address,
+                index_address,
+            ));
+
+            result_address
+        }
+        SyntaxComponent::Call(name) => {
+            log::trace!("extracting address for a call");
+            generate_call(value_ast, code, symbol_table)?.ok_or(code_generation_error(
+                ErrorCode::TypeMismatch,
+                span,
+                format!("Function `{name}` does not return a value"),
+            ))?
+        }
+        SyntaxComponent::Read => {
+            log::trace!("extracting address for a read");
+            let result_address = Address::Temp(symbol_table.new_temp());
+            code.push(Code::Call(
+                "read_num".to_string(),
+                0,
+                Some(result_address.clone()),
+            ));
+
+            result_address
+        }
+        SyntaxComponent::Conversion(target_type) => {
+            log::trace!("extracting address for a conversion to {:?}", target_type);
+            let operand = value_ast.iter().next().unwrap();
+            let operand_type = value_type(operand, symbol_table);
+            let operand_address = extract_value_address(operand, code, symbol_table)?;
+
+            let instruction = match (target_type, operand_type) {
+                (Type::Number, DataType::Integer) => Instruction::IntegerToFloat,
+                (Type::Integer, DataType::Number) => Instruction::FloatToInteger,
+                (Type::Integer, DataType::Character) => Instruction::CharacterToInteger,
+                (Type::Character, DataType::Integer) => Instruction::IntegerToCharacter,
+                // converting to the same type does nothing
+                _ => return Ok(operand_address),
+            };
+
+            // constants are converted right away
+            let converted_constant = match &operand_address {
+                Address::Constant(Value::Integer(integer)) => match instruction {
+                    Instruction::IntegerToFloat => Some(Value::Float(*integer as f64)),
+                    _ => u32::try_from(*integer)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .map(Value::Character),
+                },
+                Address::Constant(Value::Float(float)) => Some(Value::Integer(*float as i64)),
+                Address::Constant(Value::Character(character)) => {
+                    Some(Value::Integer(*character as i64))
+                }
+                _ => None,
+            };
+
+            match converted_constant {
+                Some(value) => Address::Constant(value),
+                None => {
+                    let result_address = Address::Temp(symbol_table.new_temp());
+
+                    code.push(Code::ThreeAddress(ThreeAddressCode {
+                        instruction,
+                        operand_1: operand_address,
+                        operand_2: None,
+                        result: result_address.clone(),
+                    }));
+
+                    result_address
+                }
+            }
+        }
         SyntaxComponent::Relation(relation_operation) => match relation_operation {
             Relation::GreaterThan => {
                 log::trace!("extracting address for a greater than operation");
@@ -336,10 +1248,8 @@ fn extract_value_address(
                 let right_operand = value_children.next().unwrap();
                 let result_address = Address::Temp(symbol_table.new_temp());
 
-                let left_operand_address = extract_value_address(left_operand, code, symbol_table)?;
-
-                let right_operand_address =
-                    extract_value_address(right_operand, code, symbol_table)?;
+                let (left_operand_address, right_operand_address) =
+                    extract_operand_addresses(left_operand, right_operand, code, symbol_table)?;
 
                 code.push(Code::ThreeAddress(ThreeAddressCode {
                     instruction: Instruction::GreaterThan,
@@ -357,10 +1267,8 @@ fn extract_value_address(
                 let right_operand = value_children.next().unwrap();
                 let result_address = Address::Temp(symbol_table.new_temp());
 
-                let left_operand_address = extract_value_address(left_operand, code, symbol_table)?;
-
-                let right_operand_address =
-                    extract_value_address(right_operand, code, symbol_table)?;
+                let (left_operand_address, right_operand_address) =
+                    extract_operand_addresses(left_operand, right_operand, code, symbol_table)?;
 
                 code.push(Code::ThreeAddress(ThreeAddressCode {
                     instruction: Instruction::GreaterThanOrEqual,
@@ -378,10 +1286,8 @@ fn extract_value_address(
                 let right_operand = value_children.next().unwrap();
                 let result_address = Address::Temp(symbol_table.new_temp());
 
-                let left_operand_address = extract_value_address(left_operand, code, symbol_table)?;
-
-                let right_operand_address =
-                    extract_value_address(right_operand, code, symbol_table)?;
+                let (left_operand_address, right_operand_address) =
+                    extract_operand_addresses(left_operand, right_operand, code, symbol_table)?;
 
                 code.push(Code::ThreeAddress(ThreeAddressCode {
                     instruction: Instruction::LessThan,
@@ -399,10 +1305,8 @@ fn extract_value_address(
                 let right_operand = value_children.next().unwrap();
                 let result_address = Address::Temp(symbol_table.new_temp());
 
-                let left_operand_address = extract_value_address(left_operand, code, symbol_table)?;
-
-                let right_operand_address =
-                    extract_value_address(right_operand, code, symbol_table)?;
+                let (left_operand_address, right_operand_address) =
+                    extract_operand_addresses(left_operand, right_operand, code, symbol_table)?;
 
                 code.push(Code::ThreeAddress(ThreeAddressCode {
                     instruction: Instruction::LessThanOrEqual,
@@ -420,10 +1324,8 @@ fn extract_value_address(
                 let right_operand = value_children.next().unwrap();
                 let result_address = Address::Temp(symbol_table.new_temp());
 
-                let left_operand_address = extract_value_address(left_operand, code, symbol_table)?;
-
-                let right_operand_address =
-                    extract_value_address(right_operand, code, symbol_table)?;
+                let (left_operand_address, right_operand_address) =
+                    extract_operand_addresses(left_operand, right_operand, code, symbol_table)?;
 
                 code.push(Code::ThreeAddress(ThreeAddressCode {
                     instruction: Instruction::Equal,
@@ -441,10 +1343,8 @@ fn extract_value_address(
                 let right_operand = value_children.next().unwrap();
                 let result_address = Address::Temp(symbol_table.new_temp());
 
-                let left_operand_address = extract_value_address(left_operand, code, symbol_table)?;
-
-                let right_operand_address =
-                    extract_value_address(right_operand, code, symbol_table)?;
+                let (left_operand_address, right_operand_address) =
+                    extract_operand_addresses(left_operand, right_operand, code, symbol_table)?;
 
                 code.push(Code::ThreeAddress(ThreeAddressCode {
                     instruction: Instruction::NotEqual,
@@ -458,9 +1358,13 @@ fn extract_value_address(
         },
         non_compatible => {
             log::error!("extracting address for a non-compatible operation");
-            return Err(format!(
-                "SyntaxComponent cannot be converted into address {:?}",
-                non_compatible
+            return Err(code_generation_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                format!(
+                    "SyntaxComponent cannot be converted into address {:?}",
+                    non_compatible
+                ),
             ));
         }
     };
@@ -469,19 +1373,22 @@ fn extract_value_address(
 }
 
 pub fn intermediate_code_generation(
-    ast: &Node<SyntaxComponent>,
+    ast: &Node<SyntaxNode>,
     symbol_table: &mut SymbolTable,
-) -> Result<Vec<Code>, String> {
+) -> Result<Vec<Code>, Diagnostic> {
     let mut result: Vec<Code> = vec![];
 
-    match ast.data() {
+    let span = ast.data().span;
+    match &ast.data().component {
         SyntaxComponent::Sequence => {
             log::trace!("Generating code for sequence");
             // clone for this "block"
             let mut symbol_table_clone = symbol_table.clone();
-            let mut sequence_items = ast.iter();
+            register_types(ast, &mut symbol_table_clone);
+            register_functions(ast, &mut symbol_table_clone);
+            let sequence_items = ast.iter();
 
-            while let Some(sequence_item) = sequence_items.next() {
+            for sequence_item in sequence_items {
                 let mut item_code =
                     intermediate_code_generation(sequence_item, &mut symbol_table_clone)?;
                 result.append(&mut item_code);
@@ -491,22 +1398,39 @@ pub fn intermediate_code_generation(
         }
         SyntaxComponent::If => {
             log::trace!("Generating code for if statement");
-            let after_label_string = format!("if_before_{}", symbol_table.new_if());
+            let if_id = symbol_table.new_if();
+            let else_label_string = format!("if_else_{}", if_id);
+            let after_label_string = format!("if_after_{}", if_id);
 
             let mut children = ast.iter();
             let condition = children.next().unwrap();
-            let mut condition_code = intermediate_code_generation(condition, symbol_table)?;
-            let condition_code_address = condition_code.last().unwrap().try_get_result_address()?;
+            let condition_address = extract_value_address(condition, &mut result, symbol_table)?;
 
             let body = children.next().unwrap();
             let mut body_code = intermediate_code_generation(body, symbol_table)?;
 
-            result.append(&mut condition_code);
-            result.push(Code::JumpIfFalse(
-                condition_code_address,
-                after_label_string.clone(),
-            ));
-            result.append(&mut body_code);
+            match children.next() {
+                Some(else_body) => {
+                    let mut else_body_code = intermediate_code_generation(else_body, symbol_table)?;
+
+                    result.push(Code::JumpIfFalse(
+                        condition_address,
+                        else_label_string.clone(),
+                    ));
+                    result.append(&mut body_code);
+                    // skip the else branch once the body is done
+                    result.push(Code::Jump(after_label_string.clone()));
+                    result.push(Code::Label(else_label_string));
+                    result.append(&mut else_body_code);
+                }
+                None => {
+                    result.push(Code::JumpIfFalse(
+                        condition_address,
+                        after_label_string.clone(),
+                    ));
+                    result.append(&mut body_code);
+                }
+            }
             result.push(Code::Label(after_label_string));
 
             Ok(result)
@@ -515,42 +1439,292 @@ pub fn intermediate_code_generation(
             log::trace!("Generating code for for statement");
             let for_id = symbol_table.new_for();
             let before_label_string = format!("for_before_{}", for_id);
+            let continue_label_string = format!("for_continue_{}", for_id);
             let after_label_string = format!("for_after_{}", for_id);
 
             let mut children = ast.iter();
             let pre_loop = children.next().unwrap();
             let condition = children.next().unwrap();
             let post_loop = children.next().unwrap();
+            let body = children.next().unwrap();
+
+            let mut body_symbol_table = symbol_table.loop_scope(LoopLabels {
+                break_label: after_label_string.clone(),
+                continue_label: continue_label_string.clone(),
+            });
+
+            result.append(&mut intermediate_code_generation(pre_loop, symbol_table)?);
+            result.push(Code::Label(before_label_string.clone()));
+            let condition_address = extract_value_address(condition, &mut result, symbol_table)?;
+            result.push(Code::JumpIfFalse(
+                condition_address,
+                after_label_string.clone(),
+            ));
+            result.append(&mut intermediate_code_generation(
+                body,
+                &mut body_symbol_table,
+            )?);
+            // continue runs the post-loop expression before checking the condition again
+            result.push(Code::Label(continue_label_string));
+            result.append(&mut intermediate_code_generation(post_loop, symbol_table)?);
+            result.push(Code::Jump(before_label_string));
+            result.push(Code::Label(after_label_string));
+
+            Ok(result)
+        }
+        SyntaxComponent::While => {
+            log::trace!("Generating code for while statement");
+            let while_id = symbol_table.new_while();
+            let before_label_string = format!("while_before_{}", while_id);
+            let after_label_string = format!("while_after_{}", while_id);
 
-            let mut pre_loop_code = intermediate_code_generation(pre_loop, symbol_table)?;
-            let mut condition_code = intermediate_code_generation(condition, symbol_table)?;
-            let mut post_loop_code = intermediate_code_generation(post_loop, symbol_table)?;
+            let mut children = ast.iter();
+            let condition = children.next().unwrap();
+            let body = children.next().unwrap();
+
+            result.push(Code::Label(before_label_string.clone()));
+            let condition_address = extract_value_address(condition, &mut result, symbol_table)?;
+            result.push(Code::JumpIfFalse(
+                condition_address,
+                after_label_string.clone(),
+            ));
+            result.append(&mut intermediate_code_generation(
+                body,
+                &mut symbol_table.loop_scope(LoopLabels {
+                    break_label: after_label_string.clone(),
+                    continue_label: before_label_string.clone(),
+                }),
+            )?);
+            result.push(Code::Jump(before_label_string));
+            result.push(Code::Label(after_label_string));
 
-            let condition_code_address = condition_code.last().unwrap().try_get_result_address()?;
+            Ok(result)
+        }
+        SyntaxComponent::DoWhile => {
+            log::trace!("Generating code for do while statement");
+            let do_while_id = symbol_table.new_do_while();
+            let before_label_string = format!("do_while_before_{}", do_while_id);
+            let condition_label_string = format!("do_while_condition_{}", do_while_id);
+            let after_label_string = format!("do_while_after_{}", do_while_id);
 
+            let mut children = ast.iter();
             let body = children.next().unwrap();
-            let mut body_code = intermediate_code_generation(body, symbol_table)?;
+            let condition = children.next().unwrap();
 
-            result.append(&mut pre_loop_code);
             result.push(Code::Label(before_label_string.clone()));
-            result.append(&mut condition_code);
+            result.append(&mut intermediate_code_generation(
+                body,
+                &mut symbol_table.loop_scope(LoopLabels {
+                    break_label: after_label_string.clone(),
+                    continue_label: condition_label_string.clone(),
+                }),
+            )?);
+            result.push(Code::Label(condition_label_string));
+            let condition_address = extract_value_address(condition, &mut result, symbol_table)?;
             result.push(Code::JumpIfFalse(
-                condition_code_address.clone(),
+                condition_address,
                 after_label_string.clone(),
             ));
-            result.append(&mut body_code);
-            result.append(&mut post_loop_code);
-            result.append(&mut body_code);
             result.push(Code::Jump(before_label_string));
-            result.push(Code::Label(after_label_string.clone()));
+            result.push(Code::Label(after_label_string));
+
+            Ok(result)
+        }
+        SyntaxComponent::Break | SyntaxComponent::Continue => {
+            log::trace!("Generating code for jump statement");
+            let label = if ast.data().component == SyntaxComponent::Break {
+                symbol_table.break_label()
+            } else {
+                symbol_table
+                    .enclosing_loop()
+                    .map(|labels| labels.continue_label.clone())
+            }
+            .ok_or(code_generation_error(
+                ErrorCode::JumpOutsideLoop,
+                span,
+                "Jump statement outside of a loop",
+            ))?;
+            result.push(Code::Jump(label));
+
+            Ok(result)
+        }
+        SyntaxComponent::Function(name) => {
+            log::trace!("Generating code for function {}", name);
+            let signature = FunctionSignature::from_definition(ast);
+            let entry_label = function_label(name);
+            let end_label = format!("{entry_label}_end");
+            let mut function_symbol_table =
+                symbol_table.function_scope(signature.return_type.clone());
+
+            let mut children = ast.iter();
+            let parameters = children.next().unwrap();
+            // return type
+            children.next();
+            let body = children.next().unwrap();
+
+            // the body only runs when the function is called
+            result.push(Code::Jump(end_label.clone()));
+            result.push(Code::Label(entry_label));
+
+            for (position, parameter) in parameters.iter().enumerate() {
+                result.append(&mut intermediate_code_generation(
+                    parameter,
+                    &mut function_symbol_table,
+                )?);
+
+                let parameter_name = parameter
+                    .iter()
+                    .nth(1)
+                    .unwrap()
+                    .data()
+                    .component
+                    .try_get_identifier_name(Phase::CodeGeneration)
+                    .map_err(|diagnostic| diagnostic.with_span(span))?;
+                let parameter_address = function_symbol_table
+                    .get(&parameter_name)
+                    .and_then(|symbol| symbol.location)
+                    .unwrap();
+                result.push(Code::Arg(position, parameter_address));
+            }
+
+            result.append(&mut intermediate_code_generation(
+                body,
+                &mut function_symbol_table,
+            )?);
+
+            // functions with a result always return explicitly
+            if signature.return_type.is_none() {
+                result.push(Code::Return(None));
+            }
+            result.push(Code::Label(end_label));
+
+            Ok(result)
+        }
+        SyntaxComponent::Return => {
+            log::trace!("Generating code for return statement");
+            let return_address = match ast.iter().next() {
+                Some(value) => {
+                    let return_type = symbol_table.enclosing_function().flatten();
+                    match return_type {
+                        Some(return_type) => Some(extract_converted_address(
+                            value,
+                            &return_type,
+                            &mut result,
+                            symbol_table,
+                        )?),
+                        None => Some(extract_value_address(value, &mut result, symbol_table)?),
+                    }
+                }
+                None => None,
+            };
+            result.push(Code::Return(return_address));
+
+            Ok(result)
+        }
+        SyntaxComponent::Call(_) => {
+            log::trace!("Generating code for call statement");
+            generate_call(ast, &mut result, symbol_table)?;
+
+            Ok(result)
+        }
+        SyntaxComponent::Print => {
+            log::trace!("Generating code for print statement");
+            // the runtime prints one value at a time, so it knows how to format it
+            for argument in ast.iter() {
+                let argument_type = value_type(argument, symbol_table);
+                let argument_address = extract_value_address(argument, &mut result, symbol_table)?;
+
+                result.push(Code::Param(argument_address));
+                result.push(Code::Call(print_builtin(argument_type), 1, None));
+            }
+            result.push(Code::Call("print_line".to_string(), 0, None));
+
+            Ok(result)
+        }
+        // empty statement
+        SyntaxComponent::Null => Ok(result),
+        // structs only describe the layout of their values
+        SyntaxComponent::Struct(_) | SyntaxComponent::Enum(_) => Ok(result),
+        SyntaxComponent::Switch => {
+            log::trace!("Generating code for switch statement");
+            let switch_id = symbol_table.new_switch();
+            let after_label_string = format!("switch_after_{}", switch_id);
+
+            let mut children = ast.iter();
+            let value = children.next().unwrap();
+            let is_character = value_type(value, symbol_table) == DataType::Character;
+            let value_address = extract_value_address(value, &mut result, symbol_table)?;
+
+            let cases: Vec<&Node<SyntaxNode>> = children.collect();
+            let case_labels: Vec<Label> = cases
+                .iter()
+                .enumerate()
+                .map(|(position, case)| {
+                    if case.data().component == SyntaxComponent::Default {
+                        format!("switch_default_{}", switch_id)
+                    } else {
+                        format!("switch_case_{}_{}", switch_id, position)
+                    }
+                })
+                .collect();
+            // without a default case, unmatched values skip the switch
+            let default_label = cases
+                .iter()
+                .zip(&case_labels)
+                .find(|(case, _)| case.data().component == SyntaxComponent::Default)
+                .map_or(after_label_string.clone(), |(_, label)| label.clone());
+
+            let mut targets = vec![];
+            for (case, label) in cases.iter().zip(&case_labels) {
+                let label_count = case.iter().count() - 1;
+                for case_label in case.iter().take(label_count) {
+                    let case_value = symbol_table.constant_value(case_label).unwrap();
+                    targets.push((case_value, label.clone()));
+                }
+            }
+
+            switch_dispatch(
+                value_address,
+                is_character,
+                &targets,
+                &default_label,
+                &mut result,
+                symbol_table,
+            );
+
+            // cases do not fall through, each one leaves the switch once done
+            let case_count = cases.len();
+            for (position, (case, label)) in cases.into_iter().zip(case_labels).enumerate() {
+                result.push(Code::Label(label));
+                let body = case.iter().last().unwrap();
+                result.append(&mut intermediate_code_generation(
+                    body,
+                    &mut symbol_table.switch_scope(after_label_string.clone()),
+                )?);
+                if position + 1 < case_count {
+                    result.push(Code::Jump(after_label_string.clone()));
+                }
+            }
+            result.push(Code::Label(after_label_string));
 
             Ok(result)
         }
         SyntaxComponent::Declaration => {
             log::trace!("Generating code for declaration");
             let mut children = ast.iter();
-            let data_type = children.next().unwrap().data().try_get_type()?;
-            let identifier_name = children.next().unwrap().data().try_get_identifier_name()?;
+            let declared_type = children
+                .next()
+                .unwrap()
+                .data()
+                .component
+                .try_get_type(Phase::CodeGeneration)
+                .map_err(|diagnostic| diagnostic.with_span(span))?;
+            let identifier = children.next().unwrap();
+            let identifier_name = identifier
+                .data()
+                .component
+                .try_get_identifier_name(Phase::CodeGeneration)
+                .map_err(|diagnostic| diagnostic.with_span(span))?;
             let value = children.next();
 
             let id_address = Address::Temp(symbol_table.new_temp());
@@ -559,18 +1733,37 @@ pub fn intermediate_code_generation(
                 identifier_name,
                 Symbol {
                     location: Some(id_address.clone()),
-                    data_type: match data_type {
-                        Type::Number => DataType::Number,
-                        Type::Boolean => DataType::Boolean,
-                    },
+                    data_type: DataType::from(&declared_type),
+                    length: declared_type.array_length(),
+                    span: identifier.data().span,
+                    constant: None,
                 },
             );
 
-            if value.is_some() {
-                let value_tree = value.unwrap();
-
-                let value_result_address =
-                    extract_value_address(value_tree, &mut result, symbol_table)?;
+            if let Some(array_literal) = value.filter(|_| declared_type.array_length().is_some()) {
+                for (position, element) in array_literal.iter().enumerate() {
+                    let element_address = extract_converted_address(
+                        element,
+                        &DataType::from(&declared_type),
+                        &mut result,
+                        symbol_table,
+                    )?;
+
+                    result.push(Code::IndexedStore(
+                        id_address.clone(),
+                        Address::Constant(Value::Integer(position as i64)),
+                        element_address,
+                    ));
+                }
+            } else if let (Type::Struct(_), Some(value_tree)) = (&declared_type, value) {
+                copy_struct((id_address, 0), value_tree, &mut result, symbol_table)?;
+            } else if let Some(value_tree) = value {
+                let value_result_address = extract_converted_address(
+                    value_tree,
+                    &DataType::from(&declared_type),
+                    &mut result,
+                    symbol_table,
+                )?;
 
                 result.push(Code::ThreeAddress(ThreeAddressCode {
                     instruction: Instruction::Copy,
@@ -582,13 +1775,82 @@ pub fn intermediate_code_generation(
 
             Ok(result)
         }
+        SyntaxComponent::ConstantDeclaration => {
+            log::trace!("Generating code for constant declaration");
+            let mut children = ast.iter();
+            let declared_type = children
+                .next()
+                .unwrap()
+                .data()
+                .component
+                .try_get_type(Phase::CodeGeneration)
+                .map_err(|diagnostic| diagnostic.with_span(span))?;
+            let identifier = children.next().unwrap();
+            let identifier_name = identifier
+                .data()
+                .component
+                .try_get_identifier_name(Phase::CodeGeneration)
+                .map_err(|diagnostic| diagnostic.with_span(span))?;
+            let value = children.next().unwrap();
+
+            let constant = symbol_table.evaluate_constant(value).map_err(|message| {
+                code_generation_error(ErrorCode::NonConstantValue, value.data().span, message)
+            })?;
+            let constant = coerce_constant(constant, &declared_type);
+
+            // uses of the constant are replaced by its value, nothing is stored
+            symbol_table.insert(
+                identifier_name,
+                Symbol {
+                    location: Some(Address::Constant(constant.clone())),
+                    data_type: DataType::from(&declared_type),
+                    length: None,
+                    span: identifier.data().span,
+                    constant: Some(constant),
+                },
+            );
+
+            Ok(result)
+        }
         SyntaxComponent::Assignment => {
             log::trace!("Generating code for assignment");
             let mut children = ast.iter();
             let target_tree = children.next().unwrap();
-            let target_address = extract_value_address(target_tree, &mut result, symbol_table)?;
             let value_tree = children.next().unwrap();
-            let value_address = extract_value_address(value_tree, &mut result, symbol_table)?;
+
+            if let DataType::Struct(_) = value_type(target_tree, symbol_table) {
+                let target = extract_struct_address(target_tree, symbol_table)?;
+                copy_struct(target, value_tree, &mut result, symbol_table)?;
+
+                return Ok(result);
+            }
+
+            if target_tree.data().component.is_element_access() {
+                let (array_address, index_address) =
+                    extract_element_addresses(target_tree, &mut result, symbol_table)?;
+                let value_address = extract_converted_address(
+                    value_tree,
+                    &value_type(target_tree, symbol_table),
+                    &mut result,
+                    symbol_table,
+                )?;
+
+                result.push(Code::IndexedStore(
+                    array_address,
+                    index_address,
+                    value_address,
+                ));
+
+                return Ok(result);
+            }
+
+            let target_address = extract_value_address(target_tree, &mut result, symbol_table)?;
+            let value_address = extract_converted_address(
+                value_tree,
+                &value_type(target_tree, symbol_table),
+                &mut result,
+                symbol_table,
+            )?;
 
             result.push(Code::ThreeAddress(ThreeAddressCode {
                 instruction: Instruction::Copy,
@@ -601,16 +1863,316 @@ pub fn intermediate_code_generation(
         }
         SyntaxComponent::Relation(_)
         | SyntaxComponent::BinaryOperation(_)
+        | SyntaxComponent::LogicalOperation(_)
+        | SyntaxComponent::Conditional
         | SyntaxComponent::UnaryOperation(_)
+        | SyntaxComponent::Conversion(_)
+        | SyntaxComponent::Read
+        | SyntaxComponent::Index
+        | SyntaxComponent::Member(_)
         | SyntaxComponent::Identifier(_) => {
             log::trace!("Generating code for valuable");
             let _address = extract_value_address(ast, &mut result, symbol_table)?;
             Ok(result)
         }
 
-        SyntaxComponent::Type(_) | SyntaxComponent::Null | SyntaxComponent::Constant(_) => Err(
-            "Recursed too far bro you shouldn't be generating code for a constant or null or type"
-                .into(),
-        ),
+        SyntaxComponent::Type(_)
+        | SyntaxComponent::Parameters
+        | SyntaxComponent::ArrayLiteral
+        | SyntaxComponent::Case
+        | SyntaxComponent::Default
+        | SyntaxComponent::Constant(_)
+        | SyntaxComponent::Error(_) => Err(code_generation_error(
+            ErrorCode::MalformedSyntaxTree,
+            span,
+            "Recursed too far bro you shouldn't be generating code for a constant or type",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lexical_analysis::lexical_analysis, semantic_analysis::semantic_analysis,
+        syntax_analysis::syntax_analysis,
+    };
+
+    // one line per instruction, as printed by the compiler
+    fn generate(source: &str) -> Vec<String> {
+        let (mut tokens, diagnostics) = lexical_analysis(source.chars());
+        assert_eq!(diagnostics, vec![]);
+        let (tree, diagnostics) = syntax_analysis(&mut tokens);
+        assert_eq!(diagnostics, vec![]);
+        semantic_analysis(&tree, &mut SymbolTable::new()).unwrap();
+
+        intermediate_code_generation(&tree, &mut SymbolTable::new())
+            .unwrap()
+            .iter()
+            .map(|code| code.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn it_converts_int_sides_of_conditionals_to_nums() {
+        let code = generate("num x = 3.0; num sign = x < 0 ? 0 - 1 : 1;");
+
+        assert_eq!(
+            code,
+            vec![
+                "    t1 = 3.0",
+                "    t4 = t1 < 0.0",
+                "    ifFalse t4 goto conditional_else_1",
+                "    t3 = -1.0",
+                "    goto conditional_after_1",
+                "conditional_else_1:",
+                "    t3 = 1.0",
+                "conditional_after_1:",
+                "    t2 = t3",
+            ]
+        );
+    }
+
+    // dispatch of a switch on t1, with labels named after their values
+    fn dispatch(values: &[i64], is_character: bool) -> Vec<String> {
+        let targets: Vec<(i64, Label)> = values
+            .iter()
+            .map(|value| (*value, format!("case_{value}")))
+            .collect();
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.new_temp();
+        let mut code = vec![];
+
+        switch_dispatch(
+            Address::Temp(1),
+            is_character,
+            &targets,
+            &"default".to_string(),
+            &mut code,
+            &mut symbol_table,
+        );
+
+        code.iter().map(|code| code.to_string()).collect()
+    }
+
+    #[test]
+    fn it_uses_jump_tables_for_many_dense_labels() {
+        assert!(uses_jump_table(&[0, 1, 2, 3]));
+        assert!(uses_jump_table(&[1, 2, 4, 5, 8]));
+        assert!(!uses_jump_table(&[0, 1, 2]));
+        assert!(!uses_jump_table(&[1, 10, 100, 1000]));
+        assert!(!uses_jump_table(&[i64::MIN, 0, 1, i64::MAX]));
+        assert!(!uses_jump_table(&[]));
+    }
+
+    #[test]
+    fn it_dispatches_dense_switches_through_a_table() {
+        assert_eq!(
+            dispatch(&[1, 2, 4, 5], false),
+            vec![
+                "    t2 = t1 - 1",
+                "    t3 = t2 < 0",
+                "    if t3 goto default",
+                "    t4 = t2 > 4",
+                "    if t4 goto default",
+                // the missing 3 goes to the default case
+                "    jumpTable t2, [case_1, case_2, default, case_4, case_5]",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_dispatches_sparse_switches_through_comparisons() {
+        assert_eq!(
+            dispatch(&[1, 10, 100, 1000], false),
+            vec![
+                "    if t1 == 1 goto case_1",
+                "    if t1 == 10 goto case_10",
+                "    if t1 == 100 goto case_100",
+                "    if t1 == 1000 goto case_1000",
+                "    goto default",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_dispatches_character_switches() {
+        assert_eq!(
+            dispatch(&[97, 98, 99, 100], true),
+            vec![
+                "    t2 = (int) t1",
+                "    t3 = t2 - 97",
+                "    t4 = t3 < 0",
+                "    if t4 goto default",
+                "    t5 = t3 > 3",
+                "    if t5 goto default",
+                "    jumpTable t3, [case_97, case_98, case_99, case_100]",
+            ]
+        );
+        assert_eq!(
+            dispatch(&[97, 122], true),
+            vec![
+                "    if t1 == 'a' goto case_97",
+                "    if t1 == 'z' goto case_122",
+                "    goto default",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_generates_recursive_functions() {
+        let code = generate(
+            "fn fact(int n) -> int {
+                if (n <= 1) { return 1; }
+                return n * fact(n - 1);
+            }
+            print(fact(5));",
+        );
+
+        assert_eq!(
+            code,
+            vec![
+                "    goto fn_fact_end",
+                "fn_fact:",
+                "    t1 = arg 0",
+                "    t2 = t1 <= 1",
+                "    ifFalse t2 goto if_after_1",
+                "    return 1",
+                "if_after_1:",
+                "    t4 = t1 - 1",
+                "    param t4",
+                "    t5 = call fn_fact, 1",
+                "    t3 = t1 * t5",
+                "    return t3",
+                "fn_fact_end:",
+                "    param 5",
+                "    t6 = call fn_fact, 1",
+                "    param t6",
+                "    call print_int, 1",
+                "    call print_line, 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_only_checks_bounds_of_indices_unknown_at_compile_time() {
+        let code = generate(
+            "const int K = 2; int i = 1; num[3] xs;
+            xs[i] = 1.5;
+            xs[K - 1] = xs[0];",
+        );
+
+        assert_eq!(
+            code,
+            vec![
+                "    t1 = 1",
+                "    checkBounds t1, 3",
+                "    t2[t1] = 1.5",
+                "    t3 = t2[0]",
+                "    t2[1] = t3",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_short_circuits_logical_operations() {
+        let code = generate("bool a = true; bool b = false; bool c = true; bool r = a || b && c;");
+
+        // a true skips both b and c, b false skips c
+        assert_eq!(
+            code[3..],
+            [
+                "    if t1 goto logical_true_1",
+                "    goto logical_2",
+                "logical_2:",
+                "    if t2 goto logical_3",
+                "    goto logical_false_1",
+                "logical_3:",
+                "    if t3 goto logical_true_1",
+                "    goto logical_false_1",
+                "logical_true_1:",
+                "    t5 = true",
+                "    goto logical_after_1",
+                "logical_false_1:",
+                "    t5 = false",
+                "logical_after_1:",
+                "    t4 = t5",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_continues_for_loops_with_their_post_expression() {
+        let code = generate(
+            "int s = 0;
+            for (s = 0; s < 3; s++) { if (s == 1) { continue; } print(s); }",
+        );
+
+        assert_eq!(
+            code[2..],
+            [
+                "for_before_1:",
+                "    t2 = t1 < 3",
+                "    ifFalse t2 goto for_after_1",
+                "    t3 = t1 == 1",
+                "    ifFalse t3 goto if_after_1",
+                "    goto for_continue_1",
+                "if_after_1:",
+                "    param t1",
+                "    call print_int, 1",
+                "    call print_line, 0",
+                "for_continue_1:",
+                "    t1 = t1 + 1",
+                "    goto for_before_1",
+                "for_after_1:",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_breaks_out_of_switches_inside_loops() {
+        let code = generate(
+            "int x = 1;
+            while (true) { switch (x) { case 1: break; case 2: continue; } print(x); }",
+        );
+
+        assert_eq!(
+            code[1..],
+            [
+                "while_before_1:",
+                "    ifFalse true goto while_after_1",
+                "    if t1 == 1 goto switch_case_1_0",
+                "    if t1 == 2 goto switch_case_1_1",
+                "    goto switch_after_1",
+                "switch_case_1_0:",
+                // break leaves the switch, not the loop
+                "    goto switch_after_1",
+                "    goto switch_after_1",
+                "switch_case_1_1:",
+                "    goto while_before_1",
+                "switch_after_1:",
+                "    param t1",
+                "    call print_int, 1",
+                "    call print_line, 0",
+                "    goto while_before_1",
+                "while_after_1:",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_converts_int_operands_known_at_compile_time_next_to_nums() {
+        let code = generate("const int K = 4; num x = 3.0; num y = x * K; num z = x * (K + 1);");
+
+        assert_eq!(
+            code,
+            vec![
+                "    t1 = 3.0",
+                "    t3 = t1 * 4.0",
+                "    t2 = t3",
+                "    t5 = t1 * 5.0",
+                "    t4 = t5",
+            ]
+        );
     }
 }
@@ -0,0 +1,167 @@
+use std::fmt;
+
+use crate::span::Span;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    // reported without stopping the compilation
+    Warning,
+}
+
+/// Compiler pass a diagnostic was emitted by
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    Lexical,
+    Syntax,
+    Semantic,
+    CodeGeneration,
+}
+
+/**
+ * Stable identifier for each class of problem
+ *
+ * Codes must never be reused or renumbered, tools rely on them.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorCode {
+    UndeclaredIdentifier,
+    TypeMismatch,
+    InvalidAssignmentTarget,
+    UnexpectedCharacter,
+    InvalidToken,
+    UnexpectedToken,
+    UnexpectedEndOfInput,
+    ExpectedExpression,
+    ExpectedIdentifier,
+    // the tree handed to a pass does not have the shape it expects
+    MalformedSyntaxTree,
+    // break or continue outside of a loop
+    JumpOutsideLoop,
+    // function called with too many or too few arguments
+    ArgumentCountMismatch,
+    ReturnOutsideFunction,
+    // a function with a return type can end without returning a value
+    MissingReturn,
+    // a name defined twice in the same scope
+    DuplicateDefinition,
+    // constant array index outside of the array
+    IndexOutOfBounds,
+    // switch on an enum that does not handle every variant
+    NonExhaustiveSwitch,
+    // value that must be known at compile time, but is not
+    NonConstantValue,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::UndeclaredIdentifier => "E0001",
+            ErrorCode::TypeMismatch => "E0002",
+            ErrorCode::InvalidAssignmentTarget => "E0003",
+            ErrorCode::UnexpectedCharacter => "E0004",
+            ErrorCode::InvalidToken => "E0005",
+            ErrorCode::UnexpectedToken => "E0006",
+            ErrorCode::UnexpectedEndOfInput => "E0007",
+            ErrorCode::ExpectedExpression => "E0008",
+            ErrorCode::ExpectedIdentifier => "E0009",
+            ErrorCode::MalformedSyntaxTree => "E0010",
+            ErrorCode::JumpOutsideLoop => "E0011",
+            ErrorCode::ArgumentCountMismatch => "E0012",
+            ErrorCode::ReturnOutsideFunction => "E0013",
+            ErrorCode::MissingReturn => "E0014",
+            ErrorCode::DuplicateDefinition => "E0015",
+            ErrorCode::IndexOutOfBounds => "E0016",
+            ErrorCode::NonExhaustiveSwitch => "E0017",
+            ErrorCode::NonConstantValue => "E0018",
+        }
+    }
+}
+
+/// Secondary location attached to a diagnostic, e.g. where a symbol was declared
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: ErrorCode,
+    pub message: String,
+    pub span: Option<Span>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub phase: Phase,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        phase: Phase,
+        code: ErrorCode,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            code,
+            message: message.into(),
+            span: None,
+            labels: vec![],
+            notes: vec![],
+            phase,
+        }
+    }
+
+    pub fn error(phase: Phase, code: ErrorCode, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, phase, code, message)
+    }
+
+    pub fn warning(phase: Phase, code: ErrorCode, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, phase, code, message)
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            }
+        )
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}[{}]: ", self.severity, self.code.as_str())?;
+
+        if let Some(span) = self.span {
+            write!(f, "{span}: ")?;
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
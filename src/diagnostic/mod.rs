@@ -0,0 +1,6 @@
+#[allow(clippy::module_inception)]
+mod diagnostic;
+mod renderer;
+
+pub use diagnostic::*;
+pub use renderer::*;
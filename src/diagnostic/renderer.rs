@@ -0,0 +1,257 @@
+use crate::span::Span;
+
+use super::{Diagnostic, Severity};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+/**
+ * Renders diagnostics the way rustc does
+ *
+ * Example:
+ * error[E0002]: num cannot be assigned to bool
+ *  --> main.lc:3:1
+ *   |
+ * 2 | bool y = true;
+ *   |      - `y` declared here as bool
+ * 3 | y = x;
+ *   | ^^^^^^
+ */
+pub struct Renderer<'a> {
+    file_name: &'a str,
+    source: &'a str,
+    colour: bool,
+}
+
+// a span to underline, along with the message printed next to it
+struct Annotation<'a> {
+    span: Span,
+    message: Option<&'a str>,
+    is_primary: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(file_name: &'a str, source: &'a str) -> Self {
+        Self {
+            file_name,
+            source,
+            colour: false,
+        }
+    }
+
+    pub fn with_colour(mut self, colour: bool) -> Self {
+        self.colour = colour;
+        self
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.colour {
+            format!("{style}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn severity_style(severity: Severity) -> &'static str {
+        match severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let severity_style = Self::severity_style(diagnostic.severity);
+        let mut output = format!(
+            "{}{}\n",
+            self.paint(
+                severity_style,
+                &format!("{}[{}]", diagnostic.severity, diagnostic.code.as_str())
+            ),
+            self.paint(BOLD, &format!(": {}", diagnostic.message)),
+        );
+
+        let mut annotations: Vec<Annotation> = diagnostic
+            .labels
+            .iter()
+            .map(|label| Annotation {
+                span: label.span,
+                message: Some(&label.message),
+                is_primary: false,
+            })
+            .collect();
+
+        if let Some(span) = diagnostic.span {
+            annotations.push(Annotation {
+                span,
+                message: None,
+                is_primary: true,
+            });
+        }
+
+        let gutter_width = annotations
+            .iter()
+            .map(|annotation| annotation.span.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let gutter = " ".repeat(gutter_width);
+        let bar = self.paint(BLUE, "|");
+
+        if let Some(span) = diagnostic.span {
+            output.push_str(&format!(
+                "{gutter}{} {}:{}:{}\n",
+                self.paint(BLUE, "-->"),
+                self.file_name,
+                span.line,
+                span.column
+            ));
+        }
+
+        if !annotations.is_empty() {
+            output.push_str(&format!("{gutter} {bar}\n"));
+        }
+
+        annotations.sort_by_key(|annotation| (annotation.span.line, !annotation.is_primary));
+
+        let mut previous_line = None;
+        for annotation in &annotations {
+            let line_number = annotation.span.line;
+            let line = self.source_line(line_number);
+
+            if previous_line != Some(line_number) {
+                if previous_line.is_some_and(|previous| previous + 1 < line_number) {
+                    output.push_str(&format!("{}\n", self.paint(BLUE, "...")));
+                }
+                output.push_str(&format!(
+                    "{} {bar} {line}\n",
+                    self.paint(BLUE, &format!("{line_number:>gutter_width$}")),
+                ));
+                previous_line = Some(line_number);
+            }
+
+            let (marker, style) = if annotation.is_primary {
+                ("^", severity_style)
+            } else {
+                ("-", BLUE)
+            };
+            let mut underline = marker.repeat(self.underline_length(annotation.span, line));
+            if let Some(message) = annotation.message {
+                underline = format!("{underline} {message}");
+            }
+
+            output.push_str(&format!(
+                "{gutter} {bar} {}{}\n",
+                Self::indentation(line, annotation.span.column),
+                self.paint(style, &underline),
+            ));
+        }
+
+        for note in &diagnostic.notes {
+            output.push_str(&format!(
+                "{gutter} {} {}: {note}\n",
+                self.paint(BLUE, "="),
+                self.paint(BOLD, "note"),
+            ));
+        }
+
+        output
+    }
+
+    fn source_line(&self, line_number: usize) -> &str {
+        self.source
+            .lines()
+            .nth(line_number.saturating_sub(1))
+            .unwrap_or("")
+    }
+
+    // whitespace lining up with `column`, keeping tabs so the caret stays aligned
+    fn indentation(line: &str, column: usize) -> String {
+        line.chars()
+            .chain(std::iter::repeat(' '))
+            .take(column.saturating_sub(1))
+            .map(|character| if character == '\t' { '\t' } else { ' ' })
+            .collect()
+    }
+
+    // underline at least one character and never past the end of the line
+    fn underline_length(&self, span: Span, line: &str) -> usize {
+        let remaining_characters = line
+            .chars()
+            .count()
+            .saturating_sub(span.column.saturating_sub(1));
+        let span_characters = self
+            .source
+            .get(span.start..span.end)
+            .map(|text| text.chars().count())
+            .unwrap_or(0);
+
+        span_characters.min(remaining_characters).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::{ErrorCode, Phase};
+
+    #[test]
+    fn it_renders_source_snippets_with_carets() {
+        let source = "num x = 1;\nbool y = true;\ny = x;\n";
+        let diagnostic = Diagnostic::error(
+            Phase::Semantic,
+            ErrorCode::TypeMismatch,
+            "num cannot be assigned to bool",
+        )
+        .with_span(Span::new(26, 32, 3, 1))
+        .with_label(Span::new(16, 17, 2, 6), "`y` declared here as bool")
+        .with_note("values can only be assigned to variables of the same type");
+
+        let rendered = Renderer::new("main.lc", source).render(&diagnostic);
+
+        assert_eq!(
+            rendered,
+            "error[E0002]: num cannot be assigned to bool\n \
+             --> main.lc:3:1\n  \
+             |\n\
+             2 | bool y = true;\n  \
+             |      - `y` declared here as bool\n\
+             3 | y = x;\n  \
+             | ^^^^^^\n  \
+             = note: values can only be assigned to variables of the same type\n"
+        );
+    }
+
+    #[test]
+    fn it_renders_diagnostics_without_location() {
+        let diagnostic = Diagnostic::error(
+            Phase::Syntax,
+            ErrorCode::UnexpectedEndOfInput,
+            "Unexpected end of token stream",
+        );
+
+        let rendered = Renderer::new("main.lc", "").render(&diagnostic);
+
+        assert_eq!(rendered, "error[E0007]: Unexpected end of token stream\n");
+    }
+
+    #[test]
+    fn it_renders_warnings() {
+        let source = "switch (c) {}\n";
+        let diagnostic = Diagnostic::warning(
+            Phase::Semantic,
+            ErrorCode::NonExhaustiveSwitch,
+            "Switch on `Color` does not handle Red",
+        )
+        .with_span(Span::new(0, 13, 1, 1));
+
+        let rendered = Renderer::new("main.lc", source)
+            .with_colour(true)
+            .render(&diagnostic);
+
+        assert!(rendered.starts_with(&format!(
+            "{YELLOW}warning[E0017]{RESET}{BOLD}: Switch on `Color` does not handle Red"
+        )));
+    }
+}
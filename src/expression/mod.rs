@@ -1,6 +1,12 @@
 use std::iter::Peekable;
 
-use crate::{syntax_analysis::AbstractSyntaxTree, token::*};
+use crate::{
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    syntax_analysis::{
+        extend_span, new_node, AbstractSyntaxTree, SyntaxComponent, Type, UnaryOperation,
+    },
+    token::*,
+};
 
 // examples of expressions
 // 5 + 1
@@ -10,111 +16,365 @@ use crate::{syntax_analysis::AbstractSyntaxTree, token::*};
 // foo++ < (bar - (4 + 2))
 // foo++
 
-fn token_concludes_expression(token: &Token) -> bool {
+/*
+ * Expressions are parsed by precedence climbing: every operator binds its
+ * operands with some power, and operators that bind tighter are grouped first.
+ *
+ * Precedence, from loosest to tightest:
+ *
+ * | operators                               | position | associativity |
+ * |-----------------------------------------|----------|---------------|
+ * | = += -= *= /= \= %= **= &= |= ^= <<= >>= | infix    | right         |
+ * | ? :                                     | infix    | right         |
+ * | ||                                      | infix    | left          |
+ * | &&                                      | infix    | left          |
+ * | |                                       | infix    | left          |
+ * | ^                                       | infix    | left          |
+ * | &                                       | infix    | left          |
+ * | == !=                                   | infix    | left          |
+ * | < <= > >=                               | infix    | left          |
+ * | << >>                                   | infix    | left          |
+ * | + -                                     | infix    | left          |
+ * | * / \ %                                 | infix    | left          |
+ * | ! - ~ ++ --                             | prefix   |               |
+ * | **                                      | infix    | right         |
+ * | ++ -- [] .                              | postfix  |               |
+ *
+ * `\` is integer division. `**` binds tighter than prefix operators on its
+ * left, so `-2 ** 2` is `-(2 ** 2)`. `c ? a : b` is `a` when `c` holds and
+ * `b` otherwise, only the picked value is evaluated.
+ *
+ * Parentheses group sub-expressions explicitly: `foo > (5 + 1)`. Numeric
+ * and character types convert values the same way a call would: `int(foo)`,
+ * `num(bar)`, `char(65)`. `read()` is an operand as well, holding a number
+ * read from standard input, and so are function calls: `max(foo, 2)` and
+ * array literals: `[1, 2, 3]`. Elements of arrays are indexed with `foo[i]`,
+ * and fields of structs are accessed with `foo.x`.
+ */
+
+/// Binding power of infix operators on their left and right side. Left associative operators bind tighter to the right
+fn infix_binding_power(token: &Token) -> Option<(u8, u8)> {
+    let binding_power = match token {
+        Token::OperatorAssignment
+        | Token::OperatorIncreaseBy
+        | Token::OperatorDecreaseBy
+        | Token::OperatorMultiplyBy
+        | Token::OperatorDivideBy
+        | Token::OperatorIntegerDivideBy
+        | Token::OperatorModuloBy
+        | Token::OperatorPowerBy
+        | Token::OperatorBitwiseAndBy
+        | Token::OperatorBitwiseOrBy
+        | Token::OperatorBitwiseXorBy
+        | Token::OperatorShiftLeftBy
+        | Token::OperatorShiftRightBy => (11, 10),
+        Token::Question => (21, 20),
+        Token::OperatorOr => (30, 31),
+        Token::OperatorAnd => (40, 41),
+        Token::OperatorBitwiseOr => (50, 51),
+        Token::OperatorBitwiseXor => (60, 61),
+        Token::OperatorBitwiseAnd => (70, 71),
+        Token::OperatorEqual | Token::OperatorNotEqual => (80, 81),
+        Token::OperatorLessThan
+        | Token::OperatorLessThanOrEqual
+        | Token::OperatorGreaterThan
+        | Token::OperatorGreaterThanOrEqual => (90, 91),
+        Token::OperatorShiftLeft | Token::OperatorShiftRight => (100, 101),
+        Token::OperatorAddition | Token::OperatorSubtraction => (110, 111),
+        Token::OperatorMultiplication
+        | Token::OperatorDivision
+        | Token::OperatorIntegerDivision
+        | Token::OperatorModulo => (120, 121),
+        Token::OperatorPower => (161, 160),
+        _ => return None,
+    };
+
+    Some(binding_power)
+}
+
+fn prefix_binding_power(token: &Token) -> Option<u8> {
     match token {
-        Token::EOF | Token::Semicolon | Token::ParenthesisClosing => true,
-        _ => false,
+        Token::Not
+        | Token::OperatorSubtraction
+        | Token::OperatorBitwiseNot
+        | Token::OperatorIncrement
+        | Token::OperatorDecrement => Some(140),
+        _ => None,
     }
 }
 
-pub fn parse_expression(
-    input: &mut Peekable<impl Iterator<Item = Token>>,
-) -> Result<AbstractSyntaxTree, String> {
-    log::debug!("Parsing expression");
+fn postfix_binding_power(token: &Token) -> Option<u8> {
+    match token {
+        Token::OperatorIncrement
+        | Token::OperatorDecrement
+        | Token::BracketOpening
+        | Token::Dot => Some(170),
+        _ => None,
+    }
+}
 
-    let mut expression_tokens: Vec<Token> = vec![];
+fn operator_node(operator: SpannedToken) -> Result<AbstractSyntaxTree, Diagnostic> {
+    let component = operator
+        .token
+        .try_into()
+        .map_err(|diagnostic: Diagnostic| diagnostic.with_span(operator.span))?;
 
-    while let Some(token) = input.peek() {
-        if token_concludes_expression(token) {
-            break;
-        } else {
-            expression_tokens.push(input.next().unwrap());
+    Ok(new_node(component, operator.span))
+}
+
+/// Parses an operand, a parenthesized expression or a prefix operation
+fn parse_operand(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    let Some(next_token) = input.peek() else {
+        return Err(Diagnostic::error(
+            Phase::Syntax,
+            ErrorCode::UnexpectedEndOfInput,
+            "Unexpected end of token stream, expected expression",
+        ));
+    };
+
+    match &next_token.token {
+        Token::Constant(_)
+        | Token::IntegerConstant(_)
+        | Token::StringLiteral(_)
+        | Token::CharacterLiteral(_)
+        | Token::Identifier(_)
+        | Token::True
+        | Token::False => {
+            let operand = input.next().unwrap();
+
+            // an identifier followed by parentheses calls a function
+            if let Token::Identifier(name) = &operand.token {
+                if input.peek().map(|token| &token.token) == Some(&Token::ParenthesisOpening) {
+                    let mut node = new_node(SyntaxComponent::Call(name.clone()), operand.span);
+                    let closing = parse_arguments(input, &mut node)?;
+                    extend_span(&mut node, closing.span);
+
+                    return Ok(node);
+                }
+            }
+
+            Ok(new_node(operand.token.try_into()?, operand.span))
+        }
+        Token::ParenthesisOpening => {
+            let opening = input.next().unwrap();
+            let mut expression = parse_expression(input)?;
+            let closing = expect_token(input, Token::ParenthesisClosing)?;
+            extend_span(&mut expression, opening.span.to(closing.span));
+
+            Ok(expression)
         }
+        Token::NumType | Token::IntType | Token::CharType => {
+            let type_token = input.next().unwrap();
+            let target_type = match type_token.token {
+                Token::IntType => Type::Integer,
+                Token::CharType => Type::Character,
+                _ => Type::Number,
+            };
+            let mut node = new_node(SyntaxComponent::Conversion(target_type), type_token.span);
+
+            expect_token(input, Token::ParenthesisOpening)?;
+            node.push_back(parse_expression(input)?);
+            let closing = expect_token(input, Token::ParenthesisClosing)?;
+            extend_span(&mut node, closing.span);
+
+            Ok(node)
+        }
+        Token::BracketOpening => {
+            let mut node = new_node(SyntaxComponent::ArrayLiteral, next_token.span);
+            let closing = parse_list(
+                input,
+                &mut node,
+                Token::BracketOpening,
+                Token::BracketClosing,
+            )?;
+            extend_span(&mut node, closing.span);
+
+            Ok(node)
+        }
+        Token::Read => {
+            let keyword = input.next().unwrap();
+            let mut node = new_node(SyntaxComponent::Read, keyword.span);
+
+            expect_token(input, Token::ParenthesisOpening)?;
+            let closing = expect_token(input, Token::ParenthesisClosing)?;
+            extend_span(&mut node, closing.span);
+
+            Ok(node)
+        }
+        Token::Error(_) => Err(invalid_token_error(next_token)),
+        token => match prefix_binding_power(token) {
+            Some(right_binding_power) => {
+                let operator = input.next().unwrap();
+                let mut node = match operator.token {
+                    // a leading - negates its operand instead of subtracting
+                    Token::OperatorSubtraction => new_node(
+                        SyntaxComponent::UnaryOperation(UnaryOperation::Minus),
+                        operator.span,
+                    ),
+                    _ => operator_node(operator)?,
+                };
+                let operand = parse_expression_with_binding_power(input, right_binding_power)?;
+                let operand_span = operand.root().data().span;
+                node.push_back(operand);
+                extend_span(&mut node, operand_span);
+
+                Ok(node)
+            }
+            // the token is left in the stream for the enclosing statement to recover from
+            None => Err(Diagnostic::error(
+                Phase::Syntax,
+                ErrorCode::ExpectedExpression,
+                format!("Expected expression, got {}", token),
+            )
+            .with_span(next_token.span)),
+        },
     }
+}
+
+/**
+ * Parses a parenthesized list of comma separated expressions into children of
+ * `node`, as in calls: `(a, b + 1)`.
+ *
+ * Returns the closing parenthesis
+ */
+pub fn parse_arguments(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+    node: &mut AbstractSyntaxTree,
+) -> Result<SpannedToken, Diagnostic> {
+    parse_list(
+        input,
+        node,
+        Token::ParenthesisOpening,
+        Token::ParenthesisClosing,
+    )
+}
+
+/// Parses comma separated expressions between `opening` and `closing` into children of `node`, returning the closing token
+fn parse_list(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+    node: &mut AbstractSyntaxTree,
+    opening: Token,
+    closing: Token,
+) -> Result<SpannedToken, Diagnostic> {
+    expect_token(input, opening)?;
 
-    log::debug!(
-        "Parsing expression with  {} tokens",
-        expression_tokens.len()
-    );
+    if input.peek().map(|token| &token.token) != Some(&closing) {
+        loop {
+            node.push_back(parse_expression(input)?);
 
-    if expression_tokens.len() == 1 {
-        let only_token = expression_tokens[0].clone();
-        return match only_token {
-            Token::Constant(_) | Token::Identifier(_) | Token::True | Token::False => {
-                Ok(AbstractSyntaxTree::new(only_token.try_into()?))
+            if input.next_if(|token| token.token == Token::Comma).is_none() {
+                break;
             }
-            _ => Err(format!(
-                "Expected constant or identifier as operands. Got {:?}",
-                only_token
-            )),
-        };
+        }
     }
 
-    let operator_precedence = vec![
-        vec![Token::OperatorAssignment],
-        vec![Token::OperatorAddition, Token::OperatorSubtraction],
-        vec![Token::OperatorMultiplication, Token::OperatorDivision],
-        vec![
-            Token::OperatorDecrement,
-            Token::OperatorIncrement,
-            Token::OperatorGreaterThan,
-            Token::OperatorGreaterThanOrEqual,
-            Token::OperatorLessThan,
-            Token::OperatorLessThanOrEqual,
-            Token::OperatorEqual,
-        ],
-        vec![
-            Token::OperatorIncreaseBy,
-            Token::OperatorDecreaseBy,
-            Token::Not,
-        ],
-    ];
-
-    for precedence in operator_precedence {
-        for operator_type in precedence {
-            let operator_position_option = expression_tokens
-                .clone()
-                .into_iter()
-                .position(|token| token == operator_type);
-
-            if operator_position_option.is_some() {
-                let position = operator_position_option.unwrap();
-                let operator = &expression_tokens[position];
-                let mut node = AbstractSyntaxTree::new(operator.clone().try_into()?);
-
-                if operator.is_binary_operator() {
-                    let first_operand = parse_expression(
-                        &mut expression_tokens[0..position]
-                            .to_vec()
-                            .into_iter()
-                            .peekable(),
-                    )?;
-                    let second_operand = parse_expression(
-                        &mut expression_tokens[position + 1..]
-                            .to_vec()
-                            .into_iter()
-                            .peekable(),
-                    )?;
-                    node.push_back(first_operand);
-                    node.push_back(second_operand);
-                    return Ok(node);
-                } else {
-                    // operator is unary
-                    let operand_tokens: &[Token] = if position == 0 {
-                        &expression_tokens[1..]
-                    } else {
-                        &expression_tokens[0..position]
+    expect_token(input, closing)
+}
+
+/// Parses an expression whose operators bind at least as tight as `minimum_binding_power`
+fn parse_expression_with_binding_power(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+    minimum_binding_power: u8,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    let mut left_operand = parse_operand(input)?;
+
+    while let Some(next_token) = input.peek() {
+        if let Some(left_binding_power) = postfix_binding_power(&next_token.token) {
+            if left_binding_power < minimum_binding_power {
+                break;
+            }
+
+            let operand_span = left_operand.root().data().span;
+            let operator = input.next().unwrap();
+            let mut node = match operator.token {
+                Token::BracketOpening => {
+                    let mut node = new_node(SyntaxComponent::Index, operand_span);
+                    node.push_back(left_operand);
+                    node.push_back(parse_expression(input)?);
+                    let closing = expect_token(input, Token::BracketClosing)?;
+                    extend_span(&mut node, closing.span);
+
+                    node
+                }
+                Token::Dot => {
+                    let (name, name_span) = match input.next() {
+                        Some(SpannedToken {
+                            token: Token::Identifier(name),
+                            span,
+                        }) => (name, span),
+                        Some(SpannedToken { token, span }) => {
+                            return Err(Diagnostic::error(
+                                Phase::Syntax,
+                                ErrorCode::ExpectedIdentifier,
+                                format!("Expected field name after ., got {token}"),
+                            )
+                            .with_span(span))
+                        }
+                        None => {
+                            return Err(Diagnostic::error(
+                                Phase::Syntax,
+                                ErrorCode::UnexpectedEndOfInput,
+                                "Expected field name after .",
+                            ))
+                        }
                     };
-                    let mut operand_tokens_iter = operand_tokens.to_vec().into_iter().peekable();
-                    let operand = parse_expression(&mut operand_tokens_iter)?;
-                    node.push_back(operand);
+                    let mut node = new_node(SyntaxComponent::Member(name), operand_span);
+                    node.push_back(left_operand);
+                    extend_span(&mut node, name_span);
 
-                    return Ok(node);
+                    node
+                }
+                _ => {
+                    let mut node = operator_node(operator)?;
+                    node.push_back(left_operand);
+                    node
                 }
+            };
+            extend_span(&mut node, operand_span);
+
+            left_operand = node;
+        } else if let Some((left_binding_power, right_binding_power)) =
+            infix_binding_power(&next_token.token)
+        {
+            if left_binding_power < minimum_binding_power {
+                break;
             }
+
+            let operator = input.next().unwrap();
+            let left_span = left_operand.root().data().span;
+            let mut node = if operator.token == Token::Question {
+                let mut node = new_node(SyntaxComponent::Conditional, operator.span);
+                node.push_back(left_operand);
+                // the middle operand ends at the colon, so it can be any expression
+                node.push_back(parse_expression(input)?);
+                expect_token(input, Token::Colon)?;
+                node
+            } else {
+                let mut node = operator_node(operator)?;
+                node.push_back(left_operand);
+                node
+            };
+            let right_operand = parse_expression_with_binding_power(input, right_binding_power)?;
+            let span = left_span.to(right_operand.root().data().span);
+            node.push_back(right_operand);
+            extend_span(&mut node, span);
+
+            left_operand = node;
+        } else {
+            // anything but an operator concludes the expression
+            break;
         }
     }
 
-    println!("{:?}", expression_tokens);
-    Err(String::from("Unexpected end of expression"))
+    Ok(left_operand)
+}
+
+pub fn parse_expression(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    log::debug!("Parsing expression");
+
+    parse_expression_with_binding_power(input, 0)
 }
@@ -1,6 +1,10 @@
 use std::iter::Peekable;
 
-use crate::token::*;
+use crate::{
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    span::Span,
+    token::*,
+};
 
 #[derive(Debug)]
 enum TokenBuildingStateType {
@@ -14,12 +18,26 @@ enum TokenBuildingStateType {
     ComposableOperator,
     // only a-zA-Z in stack
     Alphabetic,
-    // only 0-9 in stack
+    // only 0-9 and _ in stack
     Numeric,
-    // 0-9+. in stack
+    // 0x or 0b followed by any digits of that base in stack
+    NumericRadix,
+    // 0-9*. in stack
     NumericPeriod,
-    // 0-9+.0-9+ in stack
+    // 0-9*.0-9+ in stack
     NumericFloatingPoint,
+    // a decimal number followed by e in stack
+    NumericExponent,
+    // a decimal number followed by e, an optional sign and 0-9+ in stack
+    NumericExponentDigits,
+    // " followed by the contents of a string in stack
+    StringLiteral,
+    // a string followed by \ in stack
+    StringEscape,
+    // ' followed by the contents of a character in stack
+    CharacterLiteral,
+    // a character followed by \ in stack
+    CharacterEscape,
     // / in stack
     MaybeComment,
     // // found. waiting for newline
@@ -33,18 +51,41 @@ enum TokenBuildingStateType {
 struct TokenBuildingState {
     pub state_type: TokenBuildingStateType,
     pub accumulator: String,
-    pub token_vector: Vec<Token>,
+    // location of the accumulated characters
+    pub accumulator_span: Span,
+    // location of the character being handled
+    pub cursor: Span,
+    pub token_vector: Vec<SpannedToken>,
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 macro_rules! composable_operators {
     () => {
-        '*' | '<' | '>' | '+' | '-'
+        '*' | '<' | '>' | '+' | '-' | '&' | '|' | '^' | '%' | '\\'
+    };
+}
+
+// characters that are tokens on their own
+macro_rules! single_character_tokens {
+    () => {
+        ';' | ',' | '~' | ':' | '?'
     };
 }
 
 macro_rules! grouping_characters {
     () => {
-        '{' | '}' | '(' | ')'
+        '{' | '}' | '(' | ')' | '[' | ']'
+    };
+}
+
+// characters that end a number and begin the next token
+macro_rules! numeric_terminators {
+    () => {
+        '=' | '/'
+            | '!'
+            | composable_operators!()
+            | single_character_tokens!()
+            | grouping_characters!()
     };
 }
 
@@ -55,26 +96,67 @@ fn reset_state_with_state_type(
     TokenBuildingState {
         state_type,
         accumulator: String::new(),
-        token_vector: state.token_vector,
+        ..state
     }
 }
 
-fn push_token(state: TokenBuildingState, token: Token) -> TokenBuildingState {
+fn push_token(state: TokenBuildingState, token: Token, span: Span) -> TokenBuildingState {
+    let mut token_vector = state.token_vector;
+    token_vector.push(SpannedToken::new(token, span));
+
     TokenBuildingState {
         state_type: TokenBuildingStateType::Empty,
         accumulator: String::new(),
-        token_vector: vec![&state.token_vector[..], &[token]].concat(),
+        token_vector,
+        ..state
     }
 }
 
-fn commit_accumulator(state: TokenBuildingState) -> Result<TokenBuildingState, String> {
+fn character_span(character: char, state: &TokenBuildingState) -> Span {
+    Span::new(
+        state.cursor.start,
+        state.cursor.start + character.len_utf8(),
+        state.cursor.line,
+        state.cursor.column,
+    )
+}
+
+// keeps the offending text in the token stream and records why it is invalid
+fn push_error_token(
+    state: TokenBuildingState,
+    text: String,
+    span: Span,
+    diagnostic: Diagnostic,
+) -> TokenBuildingState {
+    let mut diagnostics = state.diagnostics;
+    diagnostics.push(diagnostic);
+
+    push_token(
+        TokenBuildingState {
+            diagnostics,
+            ..state
+        },
+        Token::Error(text),
+        span,
+    )
+}
+
+fn commit_accumulator(state: TokenBuildingState) -> TokenBuildingState {
     let accumulator = state.accumulator.clone();
     if accumulator.is_empty() {
-        Ok(state)
+        state
     } else {
-        let token = Token::try_from(accumulator)?;
+        let span = state.accumulator_span;
 
-        Ok(push_token(state, token))
+        match Token::try_from(accumulator.clone()) {
+            Ok(token) => push_token(state, token, span),
+            Err(message) => push_error_token(
+                state,
+                accumulator,
+                span,
+                Diagnostic::error(Phase::Lexical, ErrorCode::InvalidToken, message).with_span(span),
+            ),
+        }
     }
 }
 
@@ -83,21 +165,25 @@ fn accumulate_character(
     state: TokenBuildingState,
     state_type: TokenBuildingStateType,
 ) -> TokenBuildingState {
+    let accumulator_span = if state.accumulator.is_empty() {
+        character_span(character, &state)
+    } else {
+        state.accumulator_span.to(character_span(character, &state))
+    };
+
     let mut accumulator = state.accumulator.clone();
     accumulator.push(character);
 
     TokenBuildingState {
         state_type,
         accumulator,
-        token_vector: state.token_vector,
+        accumulator_span,
+        ..state
     }
 }
 
-fn unexpected_character_error(
-    character: char,
-    state: TokenBuildingState,
-) -> Result<TokenBuildingState, String> {
-    Err(format!(
+fn unexpected_character_error(character: char, state: TokenBuildingState) -> TokenBuildingState {
+    let message = format!(
         "Unexpected {} after {}, with state {:?}",
         if character.is_whitespace() {
             String::from("whitespace")
@@ -107,17 +193,34 @@ fn unexpected_character_error(
         if state.accumulator.is_empty() {
             String::from("empty string")
         } else {
-            state.accumulator.into()
+            state.accumulator.clone()
         },
         state.state_type
-    ))
+    );
+
+    // the offending text is the unfinished token along with the character that broke it
+    let mut text = state.accumulator.clone();
+    let mut span = state.accumulator_span;
+    if !character.is_whitespace() {
+        text.push(character);
+        span = if state.accumulator.is_empty() {
+            character_span(character, &state)
+        } else {
+            span.to(character_span(character, &state))
+        };
+    }
+
+    let diagnostic =
+        Diagnostic::error(Phase::Lexical, ErrorCode::UnexpectedCharacter, message).with_span(span);
+
+    push_error_token(state, text, span, diagnostic)
 }
 
 fn commit_accumulator_and_begin_with_character(
     character: char,
     state: TokenBuildingState,
-) -> Result<TokenBuildingState, String> {
-    let state_after_committing_accumulator = commit_accumulator(state)?;
+) -> TokenBuildingState {
+    let state_after_committing_accumulator = commit_accumulator(state);
 
     let state_type = match character {
         '!' => TokenBuildingStateType::Not,
@@ -125,28 +228,28 @@ fn commit_accumulator_and_begin_with_character(
         '/' => TokenBuildingStateType::MaybeComment,
         composable_operators!() => TokenBuildingStateType::ComposableOperator,
         // single-character tokens result in an empty state
-        ';' | grouping_characters!() => {
-            return Ok(push_token(
+        single_character_tokens!() | grouping_characters!() => {
+            return accumulate_character_and_commit_accumulator(
+                character,
                 state_after_committing_accumulator,
-                Token::try_from(character.to_string())?,
-            ))
+            );
         }
         _ if character.is_alphabetic() => TokenBuildingStateType::Alphabetic,
         _ if character.is_numeric() => TokenBuildingStateType::Numeric,
+        // leading-dot floats such as .5
+        '.' => TokenBuildingStateType::NumericPeriod,
+        '"' => TokenBuildingStateType::StringLiteral,
+        '\'' => TokenBuildingStateType::CharacterLiteral,
         _ => return unexpected_character_error(character, state_after_committing_accumulator),
     };
 
-    return Ok(accumulate_character(
-        character,
-        state_after_committing_accumulator,
-        state_type,
-    ));
+    accumulate_character(character, state_after_committing_accumulator, state_type)
 }
 
 fn accumulate_character_and_commit_accumulator(
     character: char,
     state: TokenBuildingState,
-) -> Result<TokenBuildingState, String> {
+) -> TokenBuildingState {
     // setting state to empty because it will not matter after accumulator is committed
     commit_accumulator(accumulate_character(
         character,
@@ -155,26 +258,12 @@ fn accumulate_character_and_commit_accumulator(
     ))
 }
 
-fn commit_accumulator_and_single_character_token(
-    character: char,
-    state: TokenBuildingState,
-) -> Result<TokenBuildingState, String> {
-    let state_after_committing_accumulator = commit_accumulator(state)?;
-    Ok(push_token(
-        state_after_committing_accumulator,
-        Token::try_from(character.to_string())?,
-    ))
-}
-
-fn handle_character(
-    character: char,
-    state: TokenBuildingState,
-) -> Result<TokenBuildingState, String> {
+fn handle_character(character: char, state: TokenBuildingState) -> TokenBuildingState {
     match state.state_type {
         // character will be accumulated either way. figure out next state
         TokenBuildingStateType::Empty => {
             if character.is_whitespace() {
-                return Ok(state);
+                return state;
             }
 
             let new_token_building_state_type = match character {
@@ -182,41 +271,41 @@ fn handle_character(
                 '=' => TokenBuildingStateType::Equal,
                 '!' => TokenBuildingStateType::Not,
                 composable_operators!() => TokenBuildingStateType::ComposableOperator,
-                ';' | grouping_characters!() => {
+                single_character_tokens!() | grouping_characters!() => {
                     return accumulate_character_and_commit_accumulator(character, state)
                 }
                 _ if character.is_alphabetic() => TokenBuildingStateType::Alphabetic,
                 _ if character.is_numeric() => TokenBuildingStateType::Numeric,
+                '.' => TokenBuildingStateType::NumericPeriod,
+                '"' => TokenBuildingStateType::StringLiteral,
+                '\'' => TokenBuildingStateType::CharacterLiteral,
                 _ => return unexpected_character_error(character, state),
             };
 
-            Ok(accumulate_character(
-                character,
-                state,
-                new_token_building_state_type,
-            ))
+            accumulate_character(character, state, new_token_building_state_type)
         }
         TokenBuildingStateType::Equal => match character {
             '=' => accumulate_character_and_commit_accumulator(character, state),
-            '/' | ';' | grouping_characters!() | _ if character.is_alphanumeric() => {
-                commit_accumulator_and_single_character_token(character, state)
-            }
             _ if character.is_whitespace() => commit_accumulator(state),
-            _ => unexpected_character_error(character, state),
+            _ => commit_accumulator_and_begin_with_character(character, state),
         },
         TokenBuildingStateType::Not => match character {
             '=' => accumulate_character_and_commit_accumulator(character, state),
             _ => commit_accumulator_and_begin_with_character(character, state),
         },
         TokenBuildingStateType::ComposableOperator => match character {
-            '=' | composable_operators!() => {
+            '=' => accumulate_character_and_commit_accumulator(character, state),
+            // the arrow before the return type of a function
+            '>' if state.accumulator == "-" => {
                 accumulate_character_and_commit_accumulator(character, state)
             }
-            '/' | ';' | grouping_characters!() | _ if character.is_alphanumeric() => {
-                commit_accumulator_and_single_character_token(character, state)
+            // doubled operators such as ++ or **, which may still be followed by =
+            // other operators start a new token, as in x*-1
+            _ if state.accumulator.len() == 1 && state.accumulator.starts_with(character) => {
+                accumulate_character(character, state, TokenBuildingStateType::ComposableOperator)
             }
             _ if character.is_whitespace() => commit_accumulator(state),
-            _ => unexpected_character_error(character, state),
+            _ => commit_accumulator_and_begin_with_character(character, state),
         },
         TokenBuildingStateType::Alphabetic => {
             match character {
@@ -225,115 +314,191 @@ fn handle_character(
                 }
                 // identifiers can be composed of letters and numbers but not viceversa
                 _ if character.is_alphanumeric() => {
-                    return Ok(accumulate_character(
-                        character,
-                        state,
-                        TokenBuildingStateType::Alphabetic,
-                    ))
+                    accumulate_character(character, state, TokenBuildingStateType::Alphabetic)
                 }
                 _ if character.is_whitespace() => commit_accumulator(state),
-                _ => return unexpected_character_error(character, state),
+                _ => commit_accumulator_and_begin_with_character(character, state),
             }
         }
         TokenBuildingStateType::Numeric => match character {
-            '=' | '/' | '!' | composable_operators!() | grouping_characters!() => {
-                commit_accumulator_and_begin_with_character(character, state)
+            numeric_terminators!() => commit_accumulator_and_begin_with_character(character, state),
+            '.' => accumulate_character(character, state, TokenBuildingStateType::NumericPeriod),
+            'x' | 'X' | 'b' | 'B' if state.accumulator == "0" => {
+                accumulate_character(character, state, TokenBuildingStateType::NumericRadix)
+            }
+            'e' | 'E' => {
+                accumulate_character(character, state, TokenBuildingStateType::NumericExponent)
+            }
+            _ if character.is_numeric() || character == '_' => {
+                accumulate_character(character, state, TokenBuildingStateType::Numeric)
             }
-            '.' => Ok(accumulate_character(
-                character,
-                state,
-                TokenBuildingStateType::NumericPeriod,
-            )),
-            ';' => commit_accumulator_and_single_character_token(character, state),
-            _ if character.is_numeric() => Ok(accumulate_character(
-                character,
-                state,
-                TokenBuildingStateType::Numeric,
-            )),
             _ if character.is_whitespace() => commit_accumulator(state),
-            _ => unexpected_character_error(character, state),
+            // numbers cannot be directly followed by letters
+            _ if character.is_alphabetic() => unexpected_character_error(character, state),
+            _ => commit_accumulator_and_begin_with_character(character, state),
+        },
+        // the digits are checked once the literal is complete, see Token::try_from
+        TokenBuildingStateType::NumericRadix => match character {
+            numeric_terminators!() => commit_accumulator_and_begin_with_character(character, state),
+            _ if character.is_alphanumeric() || character == '_' => {
+                accumulate_character(character, state, TokenBuildingStateType::NumericRadix)
+            }
+            _ if character.is_whitespace() => commit_accumulator(state),
+            _ => commit_accumulator_and_begin_with_character(character, state),
         },
         TokenBuildingStateType::NumericPeriod => match character {
-            _ if character.is_numeric() => Ok(accumulate_character(
+            _ if character.is_numeric() => accumulate_character(
                 character,
                 state,
                 TokenBuildingStateType::NumericFloatingPoint,
-            )),
+            ),
+            // a period without digits after it, as in 1. or a lone .
+            numeric_terminators!() => commit_accumulator_and_begin_with_character(character, state),
+            _ if character.is_whitespace() => commit_accumulator(state),
+            // a lone period followed by a name accesses a member, as in p.x
+            _ if state.accumulator == "." => {
+                commit_accumulator_and_begin_with_character(character, state)
+            }
             _ => unexpected_character_error(character, state),
         },
         TokenBuildingStateType::NumericFloatingPoint => match character {
-            ';' | '=' | '/' | '!' | composable_operators!() | grouping_characters!() => {
-                commit_accumulator_and_begin_with_character(character, state)
+            numeric_terminators!() => commit_accumulator_and_begin_with_character(character, state),
+            'e' | 'E' => {
+                accumulate_character(character, state, TokenBuildingStateType::NumericExponent)
             }
-            _ if character.is_numeric() => Ok(accumulate_character(
+            _ if character.is_numeric() || character == '_' => accumulate_character(
                 character,
                 state,
                 TokenBuildingStateType::NumericFloatingPoint,
-            )),
+            ),
+            _ if character.is_whitespace() => commit_accumulator(state),
+            _ if character.is_alphabetic() => unexpected_character_error(character, state),
+            _ => commit_accumulator_and_begin_with_character(character, state),
+        },
+        TokenBuildingStateType::NumericExponent => match character {
+            // the sign belongs to the exponent, it is not an operator
+            _ if character.is_numeric() || character == '+' || character == '-' => {
+                accumulate_character(
+                    character,
+                    state,
+                    TokenBuildingStateType::NumericExponentDigits,
+                )
+            }
+            numeric_terminators!() => commit_accumulator_and_begin_with_character(character, state),
             _ if character.is_whitespace() => commit_accumulator(state),
             _ => unexpected_character_error(character, state),
         },
-        TokenBuildingStateType::MaybeComment => match character {
-            '/' => Ok(reset_state_with_state_type(
-                state,
-                TokenBuildingStateType::LineComment,
-            )),
-            '*' => Ok(reset_state_with_state_type(
+        TokenBuildingStateType::NumericExponentDigits => match character {
+            numeric_terminators!() => commit_accumulator_and_begin_with_character(character, state),
+            _ if character.is_numeric() || character == '_' => accumulate_character(
+                character,
                 state,
-                TokenBuildingStateType::BlockComment,
-            )),
+                TokenBuildingStateType::NumericExponentDigits,
+            ),
+            _ if character.is_whitespace() => commit_accumulator(state),
+            _ if character.is_alphabetic() => unexpected_character_error(character, state),
+            _ => commit_accumulator_and_begin_with_character(character, state),
+        },
+        // escape sequences are decoded once a quoted literal is complete, see Token::try_from
+        TokenBuildingStateType::StringLiteral => match character {
+            '"' => accumulate_character_and_commit_accumulator(character, state),
+            '\\' => accumulate_character(character, state, TokenBuildingStateType::StringEscape),
+            // strings end on the same line, the unterminated literal is reported
+            '\n' => commit_accumulator(state),
+            _ => accumulate_character(character, state, TokenBuildingStateType::StringLiteral),
+        },
+        TokenBuildingStateType::StringEscape => match character {
+            '\n' => commit_accumulator(state),
+            _ => accumulate_character(character, state, TokenBuildingStateType::StringLiteral),
+        },
+        TokenBuildingStateType::CharacterLiteral => match character {
+            '\'' => accumulate_character_and_commit_accumulator(character, state),
+            '\\' => accumulate_character(character, state, TokenBuildingStateType::CharacterEscape),
+            '\n' => commit_accumulator(state),
+            _ => accumulate_character(character, state, TokenBuildingStateType::CharacterLiteral),
+        },
+        TokenBuildingStateType::CharacterEscape => match character {
+            '\n' => commit_accumulator(state),
+            _ => accumulate_character(character, state, TokenBuildingStateType::CharacterLiteral),
+        },
+        TokenBuildingStateType::MaybeComment => match character {
+            '/' => reset_state_with_state_type(state, TokenBuildingStateType::LineComment),
+            '*' => reset_state_with_state_type(state, TokenBuildingStateType::BlockComment),
             '=' => accumulate_character_and_commit_accumulator(character, state),
             _ if character.is_whitespace() => commit_accumulator(state),
             _ => commit_accumulator_and_begin_with_character(character, state),
         },
         TokenBuildingStateType::LineComment => match character {
             // ignore everything but newlines
-            '\n' => Ok(reset_state_with_state_type(
-                state,
-                TokenBuildingStateType::Empty,
-            )),
-            _ => Ok(state),
+            '\n' => reset_state_with_state_type(state, TokenBuildingStateType::Empty),
+            _ => state,
         },
         TokenBuildingStateType::BlockComment => match character {
-            '*' => Ok(reset_state_with_state_type(
-                state,
-                TokenBuildingStateType::BlockCommentMaybeClosing,
-            )),
-            _ => Ok(state),
+            '*' => {
+                reset_state_with_state_type(state, TokenBuildingStateType::BlockCommentMaybeClosing)
+            }
+            _ => state,
         },
         TokenBuildingStateType::BlockCommentMaybeClosing => match character {
-            '/' => Ok(reset_state_with_state_type(
-                state,
-                TokenBuildingStateType::Empty,
-            )),
-            _ => Ok(state),
+            '/' => reset_state_with_state_type(state, TokenBuildingStateType::Empty),
+            _ => state,
         },
     }
 }
 
+fn advance_cursor(character: char, state: TokenBuildingState) -> TokenBuildingState {
+    let start = state.cursor.start + character.len_utf8();
+    let cursor = if character == '\n' {
+        Span::new(start, start, state.cursor.line + 1, 1)
+    } else {
+        Span::new(start, start, state.cursor.line, state.cursor.column + 1)
+    };
+
+    TokenBuildingState { cursor, ..state }
+}
+
 fn build_tokens(
-    mut input: impl Iterator<Item = char>,
-    state: TokenBuildingState,
-) -> Result<TokenBuildingState, String> {
-    match input.next() {
-        Some(character) => build_tokens(input, handle_character(character, state)?),
-        None => Ok(push_token(commit_accumulator(state)?, Token::EOF)),
+    input: impl Iterator<Item = char>,
+    mut state: TokenBuildingState,
+) -> TokenBuildingState {
+    for character in input {
+        state = advance_cursor(character, handle_character(character, state));
     }
+
+    let state = commit_accumulator(state);
+    let span = state.cursor;
+
+    push_token(state, Token::EOF, span)
 }
 
+/**
+ * Splits the input into tokens.
+ *
+ * Invalid characters do not stop the analysis; they are kept in the stream as
+ * error tokens and a diagnostic is returned for each of them.
+ */
 pub fn lexical_analysis(
     input: impl Iterator<Item = char>,
-) -> Result<Peekable<impl Iterator<Item = Token>>, String> {
+) -> (
+    Peekable<impl Iterator<Item = SpannedToken>>,
+    Vec<Diagnostic>,
+) {
     let token_building_state = build_tokens(
         input,
         TokenBuildingState {
             state_type: TokenBuildingStateType::Empty,
             accumulator: String::new(),
+            accumulator_span: Span::default(),
+            cursor: Span::new(0, 0, 1, 1),
             token_vector: vec![],
+            diagnostics: vec![],
         },
-    )?;
+    );
 
-    Ok(token_building_state.token_vector.into_iter().peekable())
+    (
+        token_building_state.token_vector.into_iter().peekable(),
+        token_building_state.diagnostics,
+    )
 }
 
 #[cfg(test)]
@@ -341,16 +506,14 @@ mod tests {
     use super::*;
 
     fn assert_input_tokenizes_as(input: String, expected_tokens: Vec<Token>) {
-        let result = lexical_analysis(input.chars());
-        assert!(result.is_ok());
-
-        let mut actual = result.unwrap();
+        let (mut actual, diagnostics) = lexical_analysis(input.chars());
+        assert_eq!(diagnostics, vec![]);
 
         for expected_token in expected_tokens {
             let next_option = actual.next();
             assert!(next_option.is_some());
 
-            let actual_token = next_option.unwrap();
+            let actual_token = next_option.unwrap().token;
             assert_eq!(actual_token, expected_token);
         }
 
@@ -366,9 +529,21 @@ mod tests {
     }
 
     #[test]
-    fn it_tokenizes_integer_constants_as_floats() {
+    fn it_tokenizes_integer_constants() {
         let input = String::from("42");
-        let expected_tokens = vec![Token::Constant(42.0), Token::EOF];
+        let expected_tokens = vec![Token::IntegerConstant(42), Token::EOF];
+
+        assert_input_tokenizes_as(input, expected_tokens);
+    }
+
+    #[test]
+    fn it_keeps_double_precision_in_float_constants() {
+        let input = String::from("16777217.25 0.1");
+        let expected_tokens = vec![
+            Token::Constant(16_777_217.25),
+            Token::Constant(0.1),
+            Token::EOF,
+        ];
 
         assert_input_tokenizes_as(input, expected_tokens);
     }
@@ -377,11 +552,11 @@ mod tests {
     fn it_interrupts_numbers_when_grouping_characters_are_reached() {
         let input = String::from("24{9821}2)");
         let expected_tokens = vec![
-            Token::Constant(24.0),
+            Token::IntegerConstant(24),
             Token::CurlyOpening,
-            Token::Constant(9821.0),
+            Token::IntegerConstant(9821),
             Token::CurlyClosing,
-            Token::Constant(2.0),
+            Token::IntegerConstant(2),
             Token::ParenthesisClosing,
             Token::EOF,
         ];
@@ -393,10 +568,10 @@ mod tests {
     fn it_breaks_numbers_apart_using_whitespace() {
         let input = String::from("42 60 42.8 231");
         let expected_tokens = vec![
-            Token::Constant(42.0),
-            Token::Constant(60.0),
+            Token::IntegerConstant(42),
+            Token::IntegerConstant(60),
             Token::Constant(42.8),
-            Token::Constant(231.0),
+            Token::IntegerConstant(231),
             Token::EOF,
         ];
 
@@ -405,8 +580,17 @@ mod tests {
 
     #[test]
     fn it_tokenizes_keywords() {
-        let input = String::from("for if");
-        let expected_tokens = vec![Token::For, Token::If, Token::EOF];
+        let input = String::from("for if else while do break continue");
+        let expected_tokens = vec![
+            Token::For,
+            Token::If,
+            Token::Else,
+            Token::While,
+            Token::Do,
+            Token::Break,
+            Token::Continue,
+            Token::EOF,
+        ];
 
         assert_input_tokenizes_as(input, expected_tokens);
     }
@@ -437,31 +621,118 @@ mod tests {
     fn it_tokenizes_operators() {
         let input = String::from(" 1 + 2++ 3 +=4 -5 -- 6-= 7* 8*= 9/ 10/= 11== 12!=13");
         let expected_tokens = vec![
-            Token::Constant(1.0),
+            Token::IntegerConstant(1),
             Token::OperatorAddition,
-            Token::Constant(2.0),
+            Token::IntegerConstant(2),
             Token::OperatorIncrement,
-            Token::Constant(3.0),
+            Token::IntegerConstant(3),
             Token::OperatorIncreaseBy,
-            Token::Constant(4.0),
+            Token::IntegerConstant(4),
             Token::OperatorSubtraction,
-            Token::Constant(5.0),
+            Token::IntegerConstant(5),
             Token::OperatorDecrement,
-            Token::Constant(6.0),
+            Token::IntegerConstant(6),
             Token::OperatorDecreaseBy,
-            Token::Constant(7.0),
+            Token::IntegerConstant(7),
             Token::OperatorMultiplication,
-            Token::Constant(8.0),
+            Token::IntegerConstant(8),
             Token::OperatorMultiplyBy,
-            Token::Constant(9.0),
+            Token::IntegerConstant(9),
             Token::OperatorDivision,
-            Token::Constant(10.0),
+            Token::IntegerConstant(10),
             Token::OperatorDivideBy,
-            Token::Constant(11.0),
+            Token::IntegerConstant(11),
             Token::OperatorEqual,
-            Token::Constant(12.0),
+            Token::IntegerConstant(12),
             Token::OperatorNotEqual,
-            Token::Constant(13.0),
+            Token::IntegerConstant(13),
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens)
+    }
+
+    #[test]
+    fn it_tokenizes_logical_operators() {
+        let input = String::from("a&&b || !c");
+        let expected_tokens = vec![
+            Token::Identifier("a".into()),
+            Token::OperatorAnd,
+            Token::Identifier("b".into()),
+            Token::OperatorOr,
+            Token::Not,
+            Token::Identifier("c".into()),
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens)
+    }
+
+    #[test]
+    fn it_tokenizes_arithmetic_operators() {
+        let input = String::from("1 % 2 %= 3 ** 4 **= 5 \\ 6 \\= 7+++8");
+        let expected_tokens = vec![
+            Token::IntegerConstant(1),
+            Token::OperatorModulo,
+            Token::IntegerConstant(2),
+            Token::OperatorModuloBy,
+            Token::IntegerConstant(3),
+            Token::OperatorPower,
+            Token::IntegerConstant(4),
+            Token::OperatorPowerBy,
+            Token::IntegerConstant(5),
+            Token::OperatorIntegerDivision,
+            Token::IntegerConstant(6),
+            Token::OperatorIntegerDivideBy,
+            Token::IntegerConstant(7),
+            Token::OperatorIncrement,
+            Token::OperatorAddition,
+            Token::IntegerConstant(8),
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens)
+    }
+
+    #[test]
+    fn it_tokenizes_bitwise_operators() {
+        let input = String::from("a&b|c^~d<<1>>2 &= |= ^= <<= >>=");
+        let expected_tokens = vec![
+            Token::Identifier("a".into()),
+            Token::OperatorBitwiseAnd,
+            Token::Identifier("b".into()),
+            Token::OperatorBitwiseOr,
+            Token::Identifier("c".into()),
+            Token::OperatorBitwiseXor,
+            Token::OperatorBitwiseNot,
+            Token::Identifier("d".into()),
+            Token::OperatorShiftLeft,
+            Token::IntegerConstant(1),
+            Token::OperatorShiftRight,
+            Token::IntegerConstant(2),
+            Token::OperatorBitwiseAndBy,
+            Token::OperatorBitwiseOrBy,
+            Token::OperatorBitwiseXorBy,
+            Token::OperatorShiftLeftBy,
+            Token::OperatorShiftRightBy,
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens)
+    }
+
+    #[test]
+    fn it_splits_operators_followed_by_unary_minus() {
+        let input = String::from("-1*-foo<-2");
+        let expected_tokens = vec![
+            Token::OperatorSubtraction,
+            Token::IntegerConstant(1),
+            Token::OperatorMultiplication,
+            Token::OperatorSubtraction,
+            Token::Identifier("foo".into()),
+            Token::OperatorLessThan,
+            Token::OperatorSubtraction,
+            Token::IntegerConstant(2),
             Token::EOF,
         ];
 
@@ -493,4 +764,327 @@ mod tests {
 
         assert_input_tokenizes_as(input, expected_tokens)
     }
+
+    #[test]
+    fn it_tracks_token_locations() {
+        let input = String::from("foo = 42;\n  bar++;");
+        let spans: Vec<Span> = lexical_analysis(input.chars())
+            .0
+            .map(|token| token.span)
+            .collect();
+
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(0, 3, 1, 1),
+                Span::new(4, 5, 1, 5),
+                Span::new(6, 8, 1, 7),
+                Span::new(8, 9, 1, 9),
+                Span::new(12, 15, 2, 3),
+                Span::new(15, 17, 2, 6),
+                Span::new(17, 18, 2, 8),
+                Span::new(18, 18, 2, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_splits_operators_from_following_identifiers() {
+        let input = String::from("foo=bar+=baz");
+        let expected_tokens = vec![
+            Token::Identifier("foo".into()),
+            Token::OperatorAssignment,
+            Token::Identifier("bar".into()),
+            Token::OperatorIncreaseBy,
+            Token::Identifier("baz".into()),
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens)
+    }
+
+    #[test]
+    fn it_tokenizes_extended_numeric_literals() {
+        let input = String::from("0x1F 0b1010 1_000_000 1.5e-3 .5 2E3;x=.25");
+        let expected_tokens = vec![
+            Token::IntegerConstant(31),
+            Token::IntegerConstant(10),
+            Token::IntegerConstant(1_000_000),
+            Token::Constant(1.5e-3),
+            Token::Constant(0.5),
+            Token::Constant(2000.0),
+            Token::Semicolon,
+            Token::Identifier("x".into()),
+            Token::OperatorAssignment,
+            Token::Constant(0.25),
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens);
+    }
+
+    #[test]
+    fn it_reports_malformed_numeric_literals() {
+        let input = String::from("1. 0x 0b12 1e+ 1__0;");
+        let (tokens, diagnostics) = lexical_analysis(input.chars());
+        let tokens: Vec<Token> = tokens.map(|token| token.token).collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Error("1.".into()),
+                Token::Error("0x".into()),
+                Token::Error("0b12".into()),
+                Token::Error("1e+".into()),
+                Token::Error("1__0".into()),
+                Token::Semicolon,
+                Token::EOF,
+            ]
+        );
+
+        let messages: Vec<&str> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Missing digits after the decimal point in '1.'",
+                "Missing digits after '0x'",
+                "Invalid digits in binary literal '0b12'",
+                "Missing exponent digits in '1e+'",
+                "Digit separators must be placed between digits in '1__0'",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_tokenizes_string_literals() {
+        let input = String::from(r#"str s = "say \"hi\"\n\tfor \\ \u{1F600}"+"";"#);
+        let expected_tokens = vec![
+            Token::StringType,
+            Token::Identifier("s".into()),
+            Token::OperatorAssignment,
+            Token::StringLiteral("say \"hi\"\n\tfor \\ \u{1F600}".into()),
+            Token::OperatorAddition,
+            Token::StringLiteral(String::new()),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens);
+    }
+
+    #[test]
+    fn it_tokenizes_character_literals() {
+        let input = String::from(r"char c = char(int('a')+1)=='\''||c<'\n';");
+        let expected_tokens = vec![
+            Token::CharType,
+            Token::Identifier("c".into()),
+            Token::OperatorAssignment,
+            Token::CharType,
+            Token::ParenthesisOpening,
+            Token::IntType,
+            Token::ParenthesisOpening,
+            Token::CharacterLiteral('a'),
+            Token::ParenthesisClosing,
+            Token::OperatorAddition,
+            Token::IntegerConstant(1),
+            Token::ParenthesisClosing,
+            Token::OperatorEqual,
+            Token::CharacterLiteral('\''),
+            Token::OperatorOr,
+            Token::Identifier("c".into()),
+            Token::OperatorLessThan,
+            Token::CharacterLiteral('\n'),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens);
+    }
+
+    #[test]
+    fn it_tokenizes_function_definitions() {
+        let input = String::from("fn f(num x)->num{return x-1;}");
+        let expected_tokens = vec![
+            Token::Fn,
+            Token::Identifier("f".into()),
+            Token::ParenthesisOpening,
+            Token::NumType,
+            Token::Identifier("x".into()),
+            Token::ParenthesisClosing,
+            Token::Arrow,
+            Token::NumType,
+            Token::CurlyOpening,
+            Token::Return,
+            Token::Identifier("x".into()),
+            Token::OperatorSubtraction,
+            Token::IntegerConstant(1),
+            Token::Semicolon,
+            Token::CurlyClosing,
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens);
+    }
+
+    #[test]
+    fn it_tokenizes_array_brackets() {
+        let input = String::from("int[3] xs=[1,2,3];xs[0]=xs[2];");
+        let expected_tokens = vec![
+            Token::IntType,
+            Token::BracketOpening,
+            Token::IntegerConstant(3),
+            Token::BracketClosing,
+            Token::Identifier("xs".into()),
+            Token::OperatorAssignment,
+            Token::BracketOpening,
+            Token::IntegerConstant(1),
+            Token::Comma,
+            Token::IntegerConstant(2),
+            Token::Comma,
+            Token::IntegerConstant(3),
+            Token::BracketClosing,
+            Token::Semicolon,
+            Token::Identifier("xs".into()),
+            Token::BracketOpening,
+            Token::IntegerConstant(0),
+            Token::BracketClosing,
+            Token::OperatorAssignment,
+            Token::Identifier("xs".into()),
+            Token::BracketOpening,
+            Token::IntegerConstant(2),
+            Token::BracketClosing,
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens);
+    }
+
+    #[test]
+    fn it_tokenizes_member_access() {
+        let input = String::from("p.x=.5;xs[0].y++;");
+        let expected_tokens = vec![
+            Token::Identifier("p".into()),
+            Token::Dot,
+            Token::Identifier("x".into()),
+            Token::OperatorAssignment,
+            Token::Constant(0.5),
+            Token::Semicolon,
+            Token::Identifier("xs".into()),
+            Token::BracketOpening,
+            Token::IntegerConstant(0),
+            Token::BracketClosing,
+            Token::Dot,
+            Token::Identifier("y".into()),
+            Token::OperatorIncrement,
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens);
+    }
+
+    #[test]
+    fn it_tokenizes_switch_statements() {
+        let input = String::from("enum E{A}switch(e){case E.A,1:default:}");
+        let expected_tokens = vec![
+            Token::Enum,
+            Token::Identifier("E".into()),
+            Token::CurlyOpening,
+            Token::Identifier("A".into()),
+            Token::CurlyClosing,
+            Token::Switch,
+            Token::ParenthesisOpening,
+            Token::Identifier("e".into()),
+            Token::ParenthesisClosing,
+            Token::CurlyOpening,
+            Token::Case,
+            Token::Identifier("E".into()),
+            Token::Dot,
+            Token::Identifier("A".into()),
+            Token::Comma,
+            Token::IntegerConstant(1),
+            Token::Colon,
+            Token::Default,
+            Token::Colon,
+            Token::CurlyClosing,
+            Token::EOF,
+        ];
+
+        assert_input_tokenizes_as(input, expected_tokens);
+    }
+
+    #[test]
+    fn it_reports_malformed_string_literals() {
+        let input = String::from("\"bad \\q\" \"open\nx;");
+        let (tokens, diagnostics) = lexical_analysis(input.chars());
+        let tokens: Vec<Token> = tokens.map(|token| token.token).collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Error("\"bad \\q\"".into()),
+                Token::Error("\"open".into()),
+                Token::Identifier("x".into()),
+                Token::Semicolon,
+                Token::EOF,
+            ]
+        );
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.code == ErrorCode::InvalidToken));
+    }
+
+    #[test]
+    fn it_reports_unexpected_characters() {
+        let input = String::from("foo @");
+        let (_, diagnostics) = lexical_analysis(input.chars());
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, ErrorCode::UnexpectedCharacter);
+        assert_eq!(diagnostics[0].phase, Phase::Lexical);
+        assert_eq!(diagnostics[0].span, Some(Span::new(4, 5, 1, 5)));
+    }
+
+    #[test]
+    fn it_keeps_tokenizing_after_invalid_characters() {
+        let input = String::from("foo@ = 1.x;\n$bar # 2;");
+        let (tokens, diagnostics) = lexical_analysis(input.chars());
+        let tokens: Vec<Token> = tokens.map(|token| token.token).collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("foo".into()),
+                Token::Error("@".into()),
+                Token::OperatorAssignment,
+                Token::Error("1.x".into()),
+                Token::Semicolon,
+                Token::Error("$".into()),
+                Token::Identifier("bar".into()),
+                Token::Error("#".into()),
+                Token::IntegerConstant(2),
+                Token::Semicolon,
+                Token::EOF,
+            ]
+        );
+
+        let spans: Vec<Span> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.span.unwrap())
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(3, 4, 1, 4),
+                Span::new(7, 10, 1, 8),
+                Span::new(12, 13, 2, 1),
+                Span::new(17, 18, 2, 6),
+            ]
+        );
+    }
 }
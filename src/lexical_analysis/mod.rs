@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 mod lexical_analysis;
 
 pub use lexical_analysis::*;
@@ -1,52 +1,95 @@
-use std::io::{stdin, Read};
+use std::{
+    env, fs,
+    io::{stderr, stdin, IsTerminal, Read},
+    process,
+};
 
+use diagnostic::{Diagnostic, Renderer};
 use symbol_table::SymbolTable;
 mod expression;
+mod span;
 mod statement;
 mod symbol_table;
 mod token;
 
 mod code_generation;
+mod diagnostic;
 mod lexical_analysis;
 mod semantic_analysis;
 mod syntax_analysis;
 
+fn report(renderer: &Renderer, diagnostic: &Diagnostic) {
+    eprintln!("{}", renderer.render(diagnostic));
+}
+
 fn main() {
     env_logger::init();
 
-    log::trace!("Starting input from standard input");
-    let char_iter = stdin().bytes().filter_map(|b| b.ok()).map(|b| b as char);
+    // source is read from the file given as first argument, or from standard input
+    let (file_name, source) = match env::args().nth(1) {
+        Some(path) => {
+            log::trace!("Starting input from {path}");
+            match fs::read_to_string(&path) {
+                Ok(source) => (path, source),
+                Err(error) => {
+                    eprintln!("error: could not read {path}: {error}");
+                    process::exit(1);
+                }
+            }
+        }
+        None => {
+            log::trace!("Starting input from standard input");
+            let mut source = String::new();
+            if let Err(error) = stdin().read_to_string(&mut source) {
+                eprintln!("error: could not read standard input: {error}");
+                process::exit(1);
+            }
+            (String::from("<stdin>"), source)
+        }
+    };
+
+    let renderer = Renderer::new(&file_name, &source)
+        .with_colour(stderr().is_terminal() && env::var_os("NO_COLOR").is_none());
 
     log::trace!("Staring lexical analysis");
 
-    let mut token_stream = match lexical_analysis::lexical_analysis(char_iter) {
-        Ok(value) => value,
-        Err(message) => {
-            log::error!("Failed lexical analysis: {message}");
-            return;
-        }
-    };
+    let (mut token_stream, lexical_diagnostics) =
+        lexical_analysis::lexical_analysis(source.chars());
+
+    for diagnostic in &lexical_diagnostics {
+        report(&renderer, diagnostic);
+    }
 
     log::trace!("Lexical analysis completed");
 
-    let abstract_syntax_tree = match syntax_analysis::syntax_analysis(&mut token_stream) {
-        Ok(value) => value,
-        Err(message) => {
-            log::error!("Failed syntax analysis: {message}");
-            return;
-        }
-    };
+    let (abstract_syntax_tree, syntax_diagnostics) =
+        syntax_analysis::syntax_analysis(&mut token_stream);
+
+    for diagnostic in &syntax_diagnostics {
+        report(&renderer, diagnostic);
+    }
 
     log::debug!("{:?}", abstract_syntax_tree);
     log::trace!("Syntax analysis completed");
 
     let mut symbol_table = SymbolTable::new();
 
-    if let Err(message) =
-        semantic_analysis::semantic_analysis(&abstract_syntax_tree, &mut symbol_table)
-    {
-        log::error!("Failed semantic analysis: {message}");
-        return;
+    // semantic analysis runs even on broken trees, to report as many errors as possible
+    let semantic_result =
+        semantic_analysis::semantic_analysis(&abstract_syntax_tree, &mut symbol_table);
+
+    // warnings found before an error are still worth knowing about
+    for warning in symbol_table.warnings() {
+        report(&renderer, &warning);
+    }
+
+    if let Err(diagnostic) = semantic_result {
+        report(&renderer, &diagnostic);
+        process::exit(1);
+    }
+
+    if !lexical_diagnostics.is_empty() || !syntax_diagnostics.is_empty() {
+        process::exit(1);
     }
 
     log::trace!("Semantic analysis completed");
@@ -58,17 +101,22 @@ fn main() {
         &mut icg_symbol_table,
     ) {
         Ok(value) => value,
-        Err(message) => {
-            log::error!("Failed semantic analysis: {message}");
-            return;
+        Err(diagnostic) => {
+            report(&renderer, &diagnostic);
+            process::exit(1);
         }
     };
 
+    // the string constant pool comes before the code that refers to it
+    for (index, string) in icg_symbol_table.string_pool().iter().enumerate() {
+        println!("str_{index} = {string:?}");
+    }
+
     println!(
         "{}",
         code_sequence
             .into_iter()
-            .map(|code| format!("{:?}", code))
+            .map(|code| code.to_string())
             .collect::<Vec<String>>()
             .join("\n")
     );
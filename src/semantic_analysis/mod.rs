@@ -1,2 +1,3 @@
+#[allow(clippy::module_inception)]
 mod semantic_analysis;
 pub use semantic_analysis::*;
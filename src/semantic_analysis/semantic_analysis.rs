@@ -1,279 +1,1845 @@
+use std::fmt;
+
 use trees::Node;
 
 use crate::{
-    symbol_table::{DataType, Symbol, SymbolTable},
-    syntax_analysis::{Constant, SyntaxComponent, Type, UnaryOperation},
+    code_generation::Value,
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    span::Span,
+    symbol_table::{
+        coerce_constant, DataType, EnumDefinition, FunctionSignature, LoopLabels, StructDefinition,
+        Symbol, SymbolTable,
+    },
+    syntax_analysis::{
+        BinaryOperation, Constant, Relation, SyntaxComponent, SyntaxNode, Type, UnaryOperation,
+    },
 };
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum ReturnType {
     Boolean,
+    Integer,
     Number,
+    String,
+    Character,
+    Struct(String),
+    Enum(String),
     Void,
 }
 
+impl From<DataType> for ReturnType {
+    fn from(data_type: DataType) -> Self {
+        match data_type {
+            DataType::Boolean => ReturnType::Boolean,
+            DataType::Integer => ReturnType::Integer,
+            DataType::Number => ReturnType::Number,
+            DataType::String => ReturnType::String,
+            DataType::Character => ReturnType::Character,
+            DataType::Struct(name) => ReturnType::Struct(name),
+            DataType::Enum(name) => ReturnType::Enum(name),
+        }
+    }
+}
+
+impl fmt::Display for ReturnType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReturnType::Boolean => write!(f, "bool"),
+            ReturnType::Integer => write!(f, "int"),
+            ReturnType::Number => write!(f, "num"),
+            ReturnType::String => write!(f, "str"),
+            ReturnType::Character => write!(f, "char"),
+            ReturnType::Struct(name) => write!(f, "struct {name}"),
+            ReturnType::Enum(name) => write!(f, "enum {name}"),
+            ReturnType::Void => write!(f, "void"),
+        }
+    }
+}
+
+impl ReturnType {
+    fn is_numeric(&self) -> bool {
+        matches!(self, ReturnType::Integer | ReturnType::Number)
+    }
+}
+
+/// Whether the statement refers to a name whose declaration failed to parse
+fn uses_broken_declaration(statement: &Node<SyntaxNode>, symbol_table: &SymbolTable) -> bool {
+    match &statement.data().component {
+        SyntaxComponent::Identifier(name) => symbol_table.is_broken_declaration(name),
+        _ => statement
+            .iter()
+            .any(|child| uses_broken_declaration(child, symbol_table)),
+    }
+}
+
+fn semantic_error(code: ErrorCode, span: Span, message: impl Into<String>) -> Diagnostic {
+    Diagnostic::error(Phase::Semantic, code, message).with_span(span)
+}
+
+/**
+ * Whether an int value can be used as a num. Int literals and ints known at
+ * compile time can, as well as conditional expressions picking between them.
+ */
+fn converts_to_number(value: &Node<SyntaxNode>, symbol_table: &SymbolTable) -> bool {
+    match value.data().component {
+        SyntaxComponent::Conditional => value
+            .iter()
+            .skip(1)
+            .all(|side| converts_to_number(side, symbol_table)),
+        _ => matches!(symbol_table.evaluate_constant(value), Ok(Value::Integer(_))),
+    }
+}
+
+/// Whether a value can be stored where `target_type` is expected, e.g. in a variable or a parameter
+fn is_assignable(
+    target_type: &ReturnType,
+    value: &Node<SyntaxNode>,
+    value_type: &ReturnType,
+    symbol_table: &SymbolTable,
+) -> bool {
+    match (target_type, value_type) {
+        (ReturnType::Number, ReturnType::Integer) => converts_to_number(value, symbol_table),
+        (target_type, value_type) => target_type == value_type,
+    }
+}
+
+/**
+ * Whether running the statement always ends with a return. Loops might not
+ * run at all, so only returns outside of them, in both branches of an if, or
+ * in every case of a switch with a default case, count.
+ */
+fn always_returns(statement: &Node<SyntaxNode>) -> bool {
+    match &statement.data().component {
+        SyntaxComponent::Return => true,
+        SyntaxComponent::Sequence => statement.iter().any(always_returns),
+        SyntaxComponent::If => {
+            statement.iter().count() == 3 && statement.iter().skip(1).all(always_returns)
+        }
+        SyntaxComponent::Switch => {
+            statement
+                .iter()
+                .any(|case| case.data().component == SyntaxComponent::Default)
+                && statement
+                    .iter()
+                    .skip(1)
+                    .all(|case| case.iter().last().is_some_and(always_returns))
+        }
+        _ => false,
+    }
+}
+
+/// Functions pass and return single values, arrays and structs are only used through variables
+fn reject_aggregate_type(type_node: &Node<SyntaxNode>) -> Result<(), Diagnostic> {
+    match &type_node.data().component {
+        SyntaxComponent::Type(Type::Array(..) | Type::Struct(_)) => Err(semantic_error(
+            ErrorCode::TypeMismatch,
+            type_node.data().span,
+            "Functions cannot take or return arrays or structs",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the struct or enum a type refers to, if any, is defined
+fn check_defined_type(
+    type_node: &Node<SyntaxNode>,
+    symbol_table: &SymbolTable,
+) -> Result<(), Diagnostic> {
+    if let SyntaxComponent::Type(type_component) = &type_node.data().component {
+        let undeclared = match type_component.element_type() {
+            Type::Struct(name) if symbol_table.types().get_struct(name).is_none() => {
+                Some(format!("Undeclared struct: {name}"))
+            }
+            Type::Enum(name) if symbol_table.types().get_enum(name).is_none() => {
+                Some(format!("Undeclared enum: {name}"))
+            }
+            _ => None,
+        };
+
+        if let Some(message) = undeclared {
+            return Err(semantic_error(
+                ErrorCode::UndeclaredIdentifier,
+                type_node.data().span,
+                message,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds an enum definition to the type registry, its variants must be distinct
+fn register_enum(
+    name: &str,
+    definition: &Node<SyntaxNode>,
+    symbol_table: &mut SymbolTable,
+) -> Result<(), Diagnostic> {
+    let mut variant_names: Vec<(&String, Span)> = vec![];
+    for variant in definition.iter() {
+        let SyntaxComponent::Identifier(variant_name) = &variant.data().component else {
+            continue;
+        };
+
+        if let Some((_, previous_span)) = variant_names
+            .iter()
+            .find(|(previous, _)| *previous == variant_name)
+        {
+            return Err(semantic_error(
+                ErrorCode::DuplicateDefinition,
+                variant.data().span,
+                format!("Variant `{variant_name}` is already declared in `{name}`"),
+            )
+            .with_label(*previous_span, "previously declared here"));
+        }
+        variant_names.push((variant_name, variant.data().span));
+    }
+
+    symbol_table.types_mut().insert_enum(
+        name.to_string(),
+        EnumDefinition::from_definition(definition),
+    );
+
+    Ok(())
+}
+
+/**
+ * Adds the structs and enums defined in a sequence to the type registry.
+ * Fields can only use types defined before, so a struct never contains itself.
+ */
+fn register_types(
+    sequence: &Node<SyntaxNode>,
+    symbol_table: &mut SymbolTable,
+) -> Result<(), Diagnostic> {
+    for statement in sequence.iter() {
+        let (SyntaxComponent::Struct(name) | SyntaxComponent::Enum(name)) =
+            &statement.data().component
+        else {
+            continue;
+        };
+
+        // structs and enums share their names
+        if let Some(previous_span) = symbol_table.types().definition_span(name) {
+            return Err(semantic_error(
+                ErrorCode::DuplicateDefinition,
+                statement.data().span,
+                format!("Type `{name}` is already defined"),
+            )
+            .with_label(previous_span, "previously defined here"));
+        }
+
+        if let SyntaxComponent::Enum(_) = statement.data().component {
+            register_enum(name, statement, symbol_table)?;
+            continue;
+        }
+
+        let mut field_names: Vec<(&String, Span)> = vec![];
+        for field in statement.iter() {
+            let mut children = field.iter();
+            let field_type = children.next().unwrap();
+            let SyntaxComponent::Identifier(field_name) =
+                &children.next().unwrap().data().component
+            else {
+                continue;
+            };
+
+            if let SyntaxComponent::Type(Type::Array(..)) = field_type.data().component {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    field_type.data().span,
+                    "Struct fields cannot be arrays",
+                ));
+            }
+            check_defined_type(field_type, symbol_table).map_err(|diagnostic| {
+                diagnostic.with_note("types must be defined before the structs using them")
+            })?;
+
+            if let Some((_, previous_span)) = field_names
+                .iter()
+                .find(|(previous, _)| *previous == field_name)
+            {
+                return Err(semantic_error(
+                    ErrorCode::DuplicateDefinition,
+                    field.data().span,
+                    format!("Field `{field_name}` is already declared in `{name}`"),
+                )
+                .with_label(*previous_span, "previously declared here"));
+            }
+            field_names.push((field_name, field.data().span));
+        }
+
+        let definition = StructDefinition::from_definition(statement, symbol_table.types());
+        symbol_table
+            .types_mut()
+            .insert_struct(name.clone(), definition);
+    }
+
+    Ok(())
+}
+
+/// Rejects changing constants and enum variants, through assignments, increments or decrements
+fn reject_immutable_target(
+    target: &Node<SyntaxNode>,
+    symbol_table: &SymbolTable,
+) -> Result<(), Diagnostic> {
+    if let Some(enum_name) = symbol_table.referenced_enum(target) {
+        return Err(semantic_error(
+            ErrorCode::InvalidAssignmentTarget,
+            target.data().span,
+            format!("Cannot assign to a variant of enum `{enum_name}`"),
+        ));
+    }
+
+    if let SyntaxComponent::Identifier(name) = &target.data().component {
+        if let Some(symbol) = symbol_table
+            .get(name)
+            .filter(|symbol| symbol.constant.is_some())
+        {
+            return Err(semantic_error(
+                ErrorCode::InvalidAssignmentTarget,
+                target.data().span,
+                format!("Cannot change constant `{name}`"),
+            )
+            .with_label(symbol.span, format!("`{name}` declared here as a constant")));
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds the functions defined in a sequence to the table, so they can be called before their definition
+fn register_functions(
+    sequence: &Node<SyntaxNode>,
+    symbol_table: &mut SymbolTable,
+) -> Result<(), Diagnostic> {
+    for statement in sequence.iter() {
+        if let SyntaxComponent::Function(name) = &statement.data().component {
+            let signature = FunctionSignature::from_definition(statement);
+
+            if let Some(previous) = symbol_table.get_function(name) {
+                return Err(semantic_error(
+                    ErrorCode::DuplicateDefinition,
+                    statement.data().span,
+                    format!("Function `{name}` is already defined"),
+                )
+                .with_label(previous.span, "previously defined here"));
+            }
+
+            symbol_table.insert_function(name.clone(), signature);
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Type both operands of an operation share. `int` and `num` never mix
+ * implicitly, values are converted with `int(x)` or `num(x)`. Ints known at
+ * compile time are the exception, they can be used as `num` values as well.
+ */
+fn common_type(
+    left_side: &Node<SyntaxNode>,
+    left_side_type: &ReturnType,
+    right_side: &Node<SyntaxNode>,
+    right_side_type: &ReturnType,
+    symbol_table: &SymbolTable,
+) -> Option<ReturnType> {
+    match (left_side_type, right_side_type) {
+        (left, right) if left == right => Some(left.clone()),
+        (ReturnType::Number, ReturnType::Integer)
+            if converts_to_number(right_side, symbol_table) =>
+        {
+            Some(ReturnType::Number)
+        }
+        (ReturnType::Integer, ReturnType::Number)
+            if converts_to_number(left_side, symbol_table) =>
+        {
+            Some(ReturnType::Number)
+        }
+        _ => None,
+    }
+}
+
 pub fn semantic_analysis(
-    abstract_syntax_tree: &Node<SyntaxComponent>,
+    abstract_syntax_tree: &Node<SyntaxNode>,
     symbol_table: &mut SymbolTable,
-) -> Result<ReturnType, String> {
+) -> Result<ReturnType, Diagnostic> {
     let mut children = abstract_syntax_tree.iter();
+    let span = abstract_syntax_tree.data().span;
 
-    let result = match abstract_syntax_tree.data() {
+    let result = match &abstract_syntax_tree.data().component {
         SyntaxComponent::If => {
-            let condition = children
-                .next()
-                .ok_or(String::from("If statement must have a condition"))?;
+            let condition = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "If statement must have a condition",
+            ))?;
 
             let condition_type = semantic_analysis(condition, symbol_table)?;
 
             if condition_type != ReturnType::Boolean {
-                return Err(String::from(
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
                     "If statement condition must evaluate to a boolean",
                 ));
             }
 
-            let body = children
-                .next()
-                .ok_or(String::from("If statement must have a condition"))?;
+            let body = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "If statement must have a body",
+            ))?;
 
             semantic_analysis(body, symbol_table)?;
 
+            if let Some(else_body) = children.next() {
+                semantic_analysis(else_body, symbol_table)?;
+            }
+
             Ok(ReturnType::Void)
         }
         SyntaxComponent::For => {
             let pre_loop_type = semantic_analysis(
-                children
-                    .next()
-                    .ok_or(String::from("If statement must have a pre-loop node"))?,
+                children.next().ok_or(semantic_error(
+                    ErrorCode::MalformedSyntaxTree,
+                    span,
+                    "If statement must have a pre-loop node",
+                ))?,
                 symbol_table,
             )?;
 
             if pre_loop_type != ReturnType::Void {
-                return Err(String::from("For statement pre-loop must evaluate to void"));
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    "For statement pre-loop must evaluate to void",
+                ));
             }
 
             let condition_type = semantic_analysis(
-                children
-                    .next()
-                    .ok_or(String::from("If statement must have a condition node"))?,
+                children.next().ok_or(semantic_error(
+                    ErrorCode::MalformedSyntaxTree,
+                    span,
+                    "If statement must have a condition node",
+                ))?,
                 symbol_table,
             )?;
 
             if condition_type != ReturnType::Boolean {
-                return Err(String::from(
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
                     "For statement condition must evaluate to boolean",
                 ));
             }
 
             let post_loop_type = semantic_analysis(
-                children
-                    .next()
-                    .ok_or(String::from("If statement must have a post-loop node"))?,
+                children.next().ok_or(semantic_error(
+                    ErrorCode::MalformedSyntaxTree,
+                    span,
+                    "If statement must have a post-loop node",
+                ))?,
                 symbol_table,
             )?;
 
             if post_loop_type != ReturnType::Void {
-                return Err(String::from(
+                return Err(semantic_error(
+                    ErrorCode::MalformedSyntaxTree,
+                    span,
                     "Post-loop return type must be a void return type",
                 ));
             }
 
             let body_type = semantic_analysis(
-                children
-                    .next()
-                    .ok_or(String::from("If statement must have a body node"))?,
-                symbol_table,
+                children.next().ok_or(semantic_error(
+                    ErrorCode::MalformedSyntaxTree,
+                    span,
+                    "If statement must have a body node",
+                ))?,
+                &mut symbol_table.loop_scope(LoopLabels::default()),
             )?;
 
             if body_type != ReturnType::Void {
-                return Err(String::from("For statement body must evaluate to boolean"));
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    "For statement body must evaluate to boolean",
+                ));
+            }
+
+            Ok(ReturnType::Void)
+        }
+        SyntaxComponent::While => {
+            let condition = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "While statement must have a condition",
+            ))?;
+
+            if semantic_analysis(condition, symbol_table)? != ReturnType::Boolean {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    condition.data().span,
+                    "While statement condition must evaluate to a boolean",
+                ));
+            }
+
+            let body = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "While statement must have a body",
+            ))?;
+
+            semantic_analysis(body, &mut symbol_table.loop_scope(LoopLabels::default()))?;
+
+            Ok(ReturnType::Void)
+        }
+        SyntaxComponent::DoWhile => {
+            let body = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Do while statement must have a body",
+            ))?;
+
+            semantic_analysis(body, &mut symbol_table.loop_scope(LoopLabels::default()))?;
+
+            let condition = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Do while statement must have a condition",
+            ))?;
+
+            if semantic_analysis(condition, symbol_table)? != ReturnType::Boolean {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    condition.data().span,
+                    "Do while statement condition must evaluate to a boolean",
+                ));
+            }
+
+            Ok(ReturnType::Void)
+        }
+        SyntaxComponent::Break => {
+            if symbol_table.break_label().is_none() {
+                return Err(semantic_error(
+                    ErrorCode::JumpOutsideLoop,
+                    span,
+                    "Break statement outside of a loop or switch",
+                ));
+            }
+
+            Ok(ReturnType::Void)
+        }
+        SyntaxComponent::Continue => {
+            if symbol_table.enclosing_loop().is_none() {
+                return Err(semantic_error(
+                    ErrorCode::JumpOutsideLoop,
+                    span,
+                    "Continue statement outside of a loop",
+                ));
+            }
+
+            Ok(ReturnType::Void)
+        }
+        SyntaxComponent::Print => {
+            for argument in children.by_ref() {
+                let argument_type = semantic_analysis(argument, symbol_table)?;
+
+                if argument_type == ReturnType::Void {
+                    return Err(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        argument.data().span,
+                        "Print arguments must have a value",
+                    ));
+                }
+
+                if let ReturnType::Struct(name) = argument_type {
+                    return Err(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        argument.data().span,
+                        format!("Cannot print struct {name}, print its fields instead"),
+                    ));
+                }
+
+                if let ReturnType::Enum(name) = argument_type {
+                    return Err(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        argument.data().span,
+                        format!("Cannot print enum {name}, convert it with int() first"),
+                    ));
+                }
+            }
+
+            Ok(ReturnType::Void)
+        }
+        SyntaxComponent::Read => Ok(ReturnType::Number),
+        SyntaxComponent::Function(name) => {
+            let signature = FunctionSignature::from_definition(abstract_syntax_tree);
+            let mut function_symbol_table =
+                symbol_table.function_scope(signature.return_type.clone());
+
+            let parameters = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Function must have parameters",
+            ))?;
+            semantic_analysis(parameters, &mut function_symbol_table)?;
+
+            if let Some(return_type) = children.next() {
+                reject_aggregate_type(return_type)?;
+            }
+
+            let body = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Function must have a body",
+            ))?;
+            semantic_analysis(body, &mut function_symbol_table)?;
+
+            if let Some(return_type) = signature.return_type {
+                if !always_returns(body) {
+                    return Err(semantic_error(
+                        ErrorCode::MissingReturn,
+                        span,
+                        format!(
+                            "Function `{name}` must return a value of type {} on every path",
+                            return_type
+                        ),
+                    )
+                    .with_note("add a return statement at the end of the function body"));
+                }
+            }
+
+            Ok(ReturnType::Void)
+        }
+        SyntaxComponent::Parameters => {
+            // parameters are declared in the function scope
+            for parameter in children.by_ref() {
+                if let Some(parameter_type) = parameter.iter().next() {
+                    reject_aggregate_type(parameter_type)?;
+                }
+                semantic_analysis(parameter, symbol_table)?;
+            }
+
+            Ok(ReturnType::Void)
+        }
+        SyntaxComponent::Return => {
+            let return_type = symbol_table.enclosing_function().ok_or(semantic_error(
+                ErrorCode::ReturnOutsideFunction,
+                span,
+                "Return statement outside of a function",
+            ))?;
+
+            match (return_type, children.next()) {
+                (Some(return_type), Some(value)) => {
+                    let expected_type = ReturnType::from(return_type);
+                    let value_type = semantic_analysis(value, symbol_table)?;
+
+                    if !is_assignable(&expected_type, value, &value_type, symbol_table) {
+                        return Err(semantic_error(
+                            ErrorCode::TypeMismatch,
+                            value.data().span,
+                            format!(
+                                "Cannot return {} from a function returning {}",
+                                value_type, expected_type
+                            ),
+                        ));
+                    }
+                }
+                (Some(return_type), None) => {
+                    return Err(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        span,
+                        format!("Expected a value of type {} to return", return_type),
+                    ))
+                }
+                (None, Some(value)) => {
+                    return Err(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        value.data().span,
+                        "Cannot return a value from a function without a return type",
+                    ))
+                }
+                (None, None) => {}
             }
 
             Ok(ReturnType::Void)
         }
+        SyntaxComponent::Call(name) => {
+            let signature = symbol_table.get_function(name).ok_or(semantic_error(
+                ErrorCode::UndeclaredIdentifier,
+                span,
+                format!("Undeclared function: {}", name),
+            ))?;
+
+            let arguments: Vec<&Node<SyntaxNode>> = children.by_ref().collect();
+            if arguments.len() != signature.parameters.len() {
+                return Err(semantic_error(
+                    ErrorCode::ArgumentCountMismatch,
+                    span,
+                    format!(
+                        "Function `{name}` takes {} arguments, got {}",
+                        signature.parameters.len(),
+                        arguments.len()
+                    ),
+                )
+                .with_label(signature.span, "defined here"));
+            }
+
+            for (argument, parameter_type) in arguments.into_iter().zip(signature.parameters) {
+                let argument_type = semantic_analysis(argument, symbol_table)?;
+                let parameter_type = ReturnType::from(parameter_type);
+
+                if !is_assignable(&parameter_type, argument, &argument_type, symbol_table) {
+                    return Err(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        argument.data().span,
+                        format!(
+                            "Expected {} argument for `{name}`, got {}",
+                            parameter_type, argument_type
+                        ),
+                    )
+                    .with_label(signature.span, "defined here"));
+                }
+            }
+
+            Ok(signature
+                .return_type
+                .map_or(ReturnType::Void, ReturnType::from))
+        }
         SyntaxComponent::Null => {
             if abstract_syntax_tree.has_no_child() {
                 Ok(ReturnType::Void)
             } else {
-                Err("Null node must not have any children".into())
+                Err(semantic_error(
+                    ErrorCode::MalformedSyntaxTree,
+                    span,
+                    "Null node must not have any children",
+                ))
             }
         }
         SyntaxComponent::Sequence => {
             let mut inherited_symbol_table = symbol_table.clone();
+            register_types(abstract_syntax_tree, &mut inherited_symbol_table)?;
+            register_functions(abstract_syntax_tree, &mut inherited_symbol_table)?;
             for child in children {
+                // the broken declaration was already reported, its uses would only add noise
+                if uses_broken_declaration(child, &inherited_symbol_table) {
+                    continue;
+                }
                 semantic_analysis(child, &mut inherited_symbol_table)?;
             }
             // don't check for more children, avoid borrow checker error
             return Ok(ReturnType::Void);
         }
         SyntaxComponent::Assignment => {
-            let left_side = children
-                .next()
-                .ok_or(String::from("Assignment must have a left side"))?;
+            let left_side = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Assignment must have a left side",
+            ))?;
 
-            if !left_side.data().is_identifier() {
-                return Err(format!(
-                    "Expected assignment to identifier, got {:?}",
-                    left_side.data(),
+            if !left_side.data().component.is_assignment_target() {
+                return Err(semantic_error(
+                    ErrorCode::InvalidAssignmentTarget,
+                    left_side.data().span,
+                    format!(
+                        "Expected assignment to identifier or array element, got {:?}",
+                        left_side.data().component
+                    ),
                 ));
             }
 
+            reject_immutable_target(left_side, symbol_table)?;
+
             let left_side_type = semantic_analysis(left_side, symbol_table)?;
-            let right_side_type = semantic_analysis(
-                children
-                    .next()
-                    .ok_or(String::from("Assignment must have a right side"))?,
-                symbol_table,
-            )?;
+            let right_side = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Assignment must have a right side",
+            ))?;
+            let right_side_type = semantic_analysis(right_side, symbol_table)?;
 
-            if left_side_type != right_side_type {
-                Err(format!(
-                    "{:?} cannot be assigned to {:?}",
-                    left_side_type, right_side_type
-                ))
+            if !is_assignable(&left_side_type, right_side, &right_side_type, symbol_table) {
+                let mut diagnostic = semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    format!(
+                        "{} cannot be assigned to {}",
+                        right_side_type, left_side_type
+                    ),
+                );
+
+                if let SyntaxComponent::Identifier(name) = &left_side.data().component {
+                    if let Some(symbol) = symbol_table.get(name) {
+                        diagnostic = diagnostic.with_label(
+                            symbol.span,
+                            format!("`{name}` declared here as {}", symbol.data_type),
+                        );
+                    }
+                }
+
+                Err(diagnostic)
             } else {
                 Ok(ReturnType::Void)
             }
         }
-        SyntaxComponent::Relation(_) => {
-            let left_side_type = semantic_analysis(
-                children
-                    .next()
-                    .ok_or(String::from("Relation operator must have a left side"))?,
-                symbol_table,
-            )?;
+        SyntaxComponent::Relation(relation) => {
+            let left_side = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Relation operator must have a left side",
+            ))?;
+            let left_side_type = semantic_analysis(left_side, symbol_table)?;
 
-            let right_side_type = semantic_analysis(
-                children
-                    .next()
-                    .ok_or(String::from("Relation operator must have a right side"))?,
-                symbol_table,
-            )?;
+            let right_side = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Relation operator must have a right side",
+            ))?;
+            let right_side_type = semantic_analysis(right_side, symbol_table)?;
 
-            if left_side_type != right_side_type {
-                Err(format!(
-                    "{:?} cannot be compared to {:?}",
-                    left_side_type, right_side_type
+            // variants have no order, only equality is meaningful
+            if matches!(left_side_type, ReturnType::Enum(_))
+                && !matches!(relation, Relation::EqualTo | Relation::NotEqualTo)
+            {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    "Enum values can only be compared with == and !=",
+                ));
+            }
+
+            if matches!(left_side_type, ReturnType::Void | ReturnType::Struct(_))
+                || common_type(
+                    left_side,
+                    &left_side_type,
+                    right_side,
+                    &right_side_type,
+                    symbol_table,
+                )
+                .is_none()
+            {
+                Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    format!(
+                        "{} cannot be compared to {}",
+                        left_side_type, right_side_type
+                    ),
                 ))
             } else {
                 Ok(ReturnType::Boolean)
             }
         }
-        SyntaxComponent::BinaryOperation(_) => {
+        SyntaxComponent::BinaryOperation(binary_operation) => {
+            let left_side = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Binary operator must have a left side",
+            ))?;
+
+            if binary_operation.is_compound_assignment()
+                && !left_side.data().component.is_assignment_target()
+            {
+                return Err(semantic_error(
+                    ErrorCode::InvalidAssignmentTarget,
+                    left_side.data().span,
+                    format!(
+                        "Expected assignment to identifier or array element, got {:?}",
+                        left_side.data().component
+                    ),
+                ));
+            }
+
+            if binary_operation.is_compound_assignment() {
+                reject_immutable_target(left_side, symbol_table)?;
+            }
+
+            let left_side_type = semantic_analysis(left_side, symbol_table)?;
+
+            let right_side = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Binary operator must have a right side",
+            ))?;
+
+            let right_side_type = semantic_analysis(right_side, symbol_table)?;
+
+            // strings are concatenated with +
+            if matches!(
+                binary_operation,
+                BinaryOperation::Add | BinaryOperation::IncreaseBy
+            ) && (left_side_type == ReturnType::String || right_side_type == ReturnType::String)
+            {
+                if left_side_type != right_side_type {
+                    return Err(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        span,
+                        format!(
+                            "Cannot concatenate {} and {}, both sides must be strings",
+                            left_side_type, right_side_type
+                        ),
+                    ));
+                }
+
+                return Ok(if binary_operation.is_compound_assignment() {
+                    ReturnType::Void
+                } else {
+                    ReturnType::String
+                });
+            }
+
+            if !left_side_type.is_numeric() || !right_side_type.is_numeric() {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    "Binary operation must be applied to numbers",
+                ));
+            }
+
+            let operation_type = common_type(
+                left_side,
+                &left_side_type,
+                right_side,
+                &right_side_type,
+                symbol_table,
+            )
+            .filter(|operation_type| {
+                // the target of a compound assignment keeps its type
+                !binary_operation.is_compound_assignment() || *operation_type == left_side_type
+            })
+            .ok_or(semantic_error(
+                ErrorCode::TypeMismatch,
+                span,
+                format!(
+                    "Cannot apply {:?} to {} and {}, convert one of them with int() or num()",
+                    binary_operation, left_side_type, right_side_type
+                ),
+            ))?;
+
+            if binary_operation.is_bitwise() && operation_type != ReturnType::Integer {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    format!("Bit operations need int operands, got {}", operation_type),
+                ));
+            }
+
+            // compound assignments are statements, like assignments
+            if binary_operation.is_compound_assignment() {
+                Ok(ReturnType::Void)
+            } else {
+                // division of two ints truncates towards zero
+                Ok(operation_type)
+            }
+        }
+        SyntaxComponent::LogicalOperation(_) => {
             let left_side_type = semantic_analysis(
-                children
-                    .next()
-                    .ok_or(String::from("Binary operator must have a left side"))?,
+                children.next().ok_or(semantic_error(
+                    ErrorCode::MalformedSyntaxTree,
+                    span,
+                    "Logical operator must have a left side",
+                ))?,
                 symbol_table,
             )?;
 
             let right_side_type = semantic_analysis(
-                children
-                    .next()
-                    .ok_or(String::from("Binary operator must have a right side"))?,
+                children.next().ok_or(semantic_error(
+                    ErrorCode::MalformedSyntaxTree,
+                    span,
+                    "Logical operator must have a right side",
+                ))?,
                 symbol_table,
             )?;
 
-            if left_side_type != ReturnType::Number || right_side_type != ReturnType::Number {
-                return Err("Binary operation must be applied to numbers".into());
+            if left_side_type != ReturnType::Boolean || right_side_type != ReturnType::Boolean {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    format!(
+                        "Logical operation must be applied to booleans, got {} and {}",
+                        left_side_type, right_side_type
+                    ),
+                ));
+            }
+
+            Ok(ReturnType::Boolean)
+        }
+        SyntaxComponent::Conditional => {
+            let condition = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Conditional expression must have a condition",
+            ))?;
+            let condition_type = semantic_analysis(condition, symbol_table)?;
+
+            if condition_type != ReturnType::Boolean {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    condition.data().span,
+                    format!(
+                        "Conditional expression condition must evaluate to a boolean, got {}",
+                        condition_type
+                    ),
+                ));
             }
 
-            Ok(ReturnType::Number)
+            let true_value = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Conditional expression must have a value for when the condition holds",
+            ))?;
+            let true_value_type = semantic_analysis(true_value, symbol_table)?;
+
+            let false_value = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Conditional expression must have a value for when the condition does not hold",
+            ))?;
+            let false_value_type = semantic_analysis(false_value, symbol_table)?;
+
+            let value_type = common_type(
+                true_value,
+                &true_value_type,
+                false_value,
+                &false_value_type,
+                symbol_table,
+            );
+
+            match value_type {
+                Some(ReturnType::Void) => Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    "Both sides of a conditional expression must have a value",
+                )),
+                // structs are copied slot by slot, which needs to know which one to copy
+                Some(ReturnType::Struct(name)) => Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    format!("Conditional expressions cannot pick a struct {name}, pick its fields instead"),
+                )),
+                Some(value_type) => Ok(value_type),
+                None => Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    format!(
+                        "Both sides of a conditional expression must have the same type, got {} and {}",
+                        true_value_type, false_value_type
+                    ),
+                )),
+            }
         }
         SyntaxComponent::UnaryOperation(unary_operation) => {
-            let operand = children
-                .next()
-                .ok_or(String::from("Unary operator must have exactly one operand"))?;
+            let operand = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Unary operator must have exactly one operand",
+            ))?;
+
+            if let UnaryOperation::Increment | UnaryOperation::Decrement = unary_operation {
+                reject_immutable_target(operand, symbol_table)?;
+            }
 
             let operand_type = semantic_analysis(operand, symbol_table)?;
 
             match unary_operation {
                 UnaryOperation::Increment | UnaryOperation::Decrement => {
-                    if operand_type != ReturnType::Number {
-                        Err("Cannot increment or decrement a non-number".into())
+                    if !operand_type.is_numeric() {
+                        Err(semantic_error(
+                            ErrorCode::TypeMismatch,
+                            span,
+                            "Cannot increment or decrement a non-number",
+                        ))
                     } else {
                         Ok(ReturnType::Void)
                     }
                 }
+                UnaryOperation::Minus => {
+                    if !operand_type.is_numeric() {
+                        Err(semantic_error(
+                            ErrorCode::TypeMismatch,
+                            span,
+                            "Cannot apply unary minus to a non-number",
+                        ))
+                    } else {
+                        Ok(operand_type)
+                    }
+                }
+                UnaryOperation::BitwiseNot => {
+                    if operand_type != ReturnType::Integer {
+                        Err(semantic_error(
+                            ErrorCode::TypeMismatch,
+                            span,
+                            format!("Cannot apply bitwise not to {}", operand_type),
+                        ))
+                    } else {
+                        Ok(ReturnType::Integer)
+                    }
+                }
                 UnaryOperation::Negation => {
                     if operand_type != ReturnType::Boolean {
-                        Err("Cannot negate a non-boolean".into())
+                        Err(semantic_error(
+                            ErrorCode::TypeMismatch,
+                            span,
+                            "Cannot negate a non-boolean",
+                        ))
                     } else {
                         Ok(ReturnType::Boolean)
                     }
                 }
             }
         }
+        SyntaxComponent::Conversion(target_type) => {
+            let operand = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Conversion must have exactly one operand",
+            ))?;
+
+            let operand_type = semantic_analysis(operand, symbol_table)?;
+
+            // characters only convert to and from their int code
+            let result_type = match (target_type, &operand_type) {
+                (
+                    Type::Integer,
+                    ReturnType::Integer
+                    | ReturnType::Number
+                    | ReturnType::Character
+                    | ReturnType::Enum(_),
+                ) => Some(ReturnType::Integer),
+                (Type::Number, ReturnType::Integer | ReturnType::Number) => {
+                    Some(ReturnType::Number)
+                }
+                (Type::Character, ReturnType::Integer | ReturnType::Character) => {
+                    Some(ReturnType::Character)
+                }
+                _ => None,
+            };
+
+            result_type.ok_or(semantic_error(
+                ErrorCode::TypeMismatch,
+                span,
+                format!("Cannot convert {} to {}", operand_type, target_type),
+            ))
+        }
         SyntaxComponent::Constant(constant) => match constant {
             Constant::Boolean(_) => Ok(ReturnType::Boolean),
+            Constant::Integer(_) => Ok(ReturnType::Integer),
             Constant::Float(_) => Ok(ReturnType::Number),
+            Constant::String(_) => Ok(ReturnType::String),
+            Constant::Character(_) => Ok(ReturnType::Character),
         },
         SyntaxComponent::Identifier(identifier) => {
-            let symbol = symbol_table
-                .get(identifier)
-                .ok_or(format!("Undeclared identifier: {}", identifier))?;
+            let symbol = symbol_table.get(identifier).ok_or(
+                semantic_error(
+                    ErrorCode::UndeclaredIdentifier,
+                    span,
+                    format!("Undeclared identifier: {}", identifier),
+                )
+                .with_note(format!(
+                    "variables must be declared before use, e.g. `num {identifier};`"
+                )),
+            )?;
+
+            if let Some(length) = symbol.length {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    format!("Array `{identifier}` can only be used through its elements"),
+                )
+                .with_label(
+                    symbol.span,
+                    format!("`{identifier}` declared here with {length} elements"),
+                )
+                .with_note(format!("index the array, e.g. `{identifier}[0]`")));
+            }
+
+            Ok(ReturnType::from(symbol.data_type))
+        }
+        SyntaxComponent::Index => {
+            let array = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Index must have an array",
+            ))?;
+            let SyntaxComponent::Identifier(name) = &array.data().component else {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    array.data().span,
+                    "Only arrays can be indexed",
+                ));
+            };
+            let symbol = symbol_table.get(name).ok_or(semantic_error(
+                ErrorCode::UndeclaredIdentifier,
+                array.data().span,
+                format!("Undeclared identifier: {}", name),
+            ))?;
+            let length = symbol.length.ok_or(
+                semantic_error(
+                    ErrorCode::TypeMismatch,
+                    array.data().span,
+                    format!("Cannot index `{name}`, only arrays can be indexed"),
+                )
+                .with_label(
+                    symbol.span,
+                    format!("`{name}` declared here as {}", symbol.data_type),
+                ),
+            )?;
+
+            let index = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Index must have an index",
+            ))?;
+            let index_type = semantic_analysis(index, symbol_table)?;
+            if index_type != ReturnType::Integer {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    index.data().span,
+                    format!("Array index must be an int, got {}", index_type),
+                ));
+            }
+
+            // other indices are checked when the program runs
+            if let Some(constant_index) = symbol_table.constant_value(index) {
+                if !(0..length as i64).contains(&constant_index) {
+                    return Err(semantic_error(
+                        ErrorCode::IndexOutOfBounds,
+                        index.data().span,
+                        format!(
+                            "Index {constant_index} is out of bounds for `{name}` of length {length}"
+                        ),
+                    )
+                    .with_label(
+                        symbol.span,
+                        format!("`{name}` declared here with {length} elements"),
+                    ));
+                }
+            }
+
+            Ok(ReturnType::from(symbol.data_type))
+        }
+        SyntaxComponent::Member(field_name) => {
+            if let Some(enum_name) = symbol_table.referenced_enum(abstract_syntax_tree) {
+                let definition = symbol_table.types().get_enum(&enum_name).unwrap();
+                if definition.position(field_name).is_none() {
+                    return Err(semantic_error(
+                        ErrorCode::UndeclaredIdentifier,
+                        span,
+                        format!("Enum `{enum_name}` has no variant `{field_name}`"),
+                    )
+                    .with_label(definition.span, format!("`{enum_name}` defined here")));
+                }
 
-            let ok_value = match symbol.data_type {
-                DataType::Number => ReturnType::Number,
-                DataType::Boolean => ReturnType::Boolean,
+                return Ok(ReturnType::Enum(enum_name));
+            }
+
+            let value = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Member access must have a struct",
+            ))?;
+            let value_type = semantic_analysis(value, symbol_table)?;
+
+            let ReturnType::Struct(struct_name) = &value_type else {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    value.data().span,
+                    format!("Only structs have fields, got {}", value_type),
+                ));
             };
+            let definition = symbol_table
+                .types()
+                .get_struct(struct_name)
+                .ok_or(semantic_error(
+                    ErrorCode::UndeclaredIdentifier,
+                    value.data().span,
+                    format!("Undeclared struct: {struct_name}"),
+                ))?;
+            let field = definition.field(field_name).ok_or(
+                semantic_error(
+                    ErrorCode::UndeclaredIdentifier,
+                    span,
+                    format!("Struct `{struct_name}` has no field `{field_name}`"),
+                )
+                .with_label(definition.span, format!("`{struct_name}` defined here")),
+            )?;
+
+            Ok(ReturnType::from(field.data_type.clone()))
+        }
+        // registered along with the other types of the sequence
+        SyntaxComponent::Struct(_) | SyntaxComponent::Enum(_) => return Ok(ReturnType::Void),
+        SyntaxComponent::Switch => {
+            let value = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Switch statement must have a value",
+            ))?;
+            let value_type = semantic_analysis(value, symbol_table)?;
+
+            if !matches!(
+                value_type,
+                ReturnType::Integer | ReturnType::Character | ReturnType::Enum(_)
+            ) {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    value.data().span,
+                    format!(
+                        "Can only switch on int, char or enum values, got {}",
+                        value_type
+                    ),
+                ));
+            }
+
+            // values of the labels seen so far, to find duplicates
+            let mut handled: Vec<(i64, Span)> = vec![];
+            let mut has_default = false;
+            for case in children.by_ref() {
+                let label_count = case.iter().count().saturating_sub(1);
+                if case.data().component == SyntaxComponent::Default {
+                    has_default = true;
+                }
+
+                for label in case.iter().take(label_count) {
+                    let label_type = semantic_analysis(label, symbol_table)?;
+                    let label_value = symbol_table.constant_value(label).ok_or(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        label.data().span,
+                        "Case labels must be known at compile time",
+                    ))?;
+
+                    if label_type != value_type {
+                        return Err(semantic_error(
+                            ErrorCode::TypeMismatch,
+                            label.data().span,
+                            format!(
+                                "Case label of type {} does not match the switched {}",
+                                label_type, value_type
+                            ),
+                        )
+                        .with_label(value.data().span, "value switched on"));
+                    }
+
+                    if let Some((_, previous_span)) = handled
+                        .iter()
+                        .find(|(previous, _)| *previous == label_value)
+                    {
+                        return Err(semantic_error(
+                            ErrorCode::DuplicateDefinition,
+                            label.data().span,
+                            "Duplicate case label",
+                        )
+                        .with_label(*previous_span, "previously handled here"));
+                    }
+                    handled.push((label_value, label.data().span));
+                }
+
+                let body = case.iter().last().ok_or(semantic_error(
+                    ErrorCode::MalformedSyntaxTree,
+                    case.data().span,
+                    "Case must have a body",
+                ))?;
+                // break leaves the switch, the label only matters to the code generation
+                semantic_analysis(body, &mut symbol_table.switch_scope(String::new()))?;
+            }
+
+            if let (ReturnType::Enum(enum_name), false) = (&value_type, has_default) {
+                let definition = symbol_table.types().get_enum(enum_name).unwrap();
+                let missing: Vec<&str> = definition
+                    .variants
+                    .iter()
+                    .enumerate()
+                    .filter(|(position, _)| {
+                        !handled
+                            .iter()
+                            .any(|(handled_value, _)| *handled_value == *position as i64)
+                    })
+                    .map(|(_, variant)| variant.as_str())
+                    .collect();
+
+                if !missing.is_empty() {
+                    symbol_table.warn(
+                        Diagnostic::warning(
+                            Phase::Semantic,
+                            ErrorCode::NonExhaustiveSwitch,
+                            format!(
+                                "Switch on `{enum_name}` does not handle {}",
+                                missing.join(", ")
+                            ),
+                        )
+                        .with_span(span)
+                        .with_note("add the missing cases or a default case"),
+                    );
+                }
+            }
 
-            Ok(ok_value)
+            Ok(ReturnType::Void)
+        }
+        // only analysed as part of their switch statement
+        SyntaxComponent::Case | SyntaxComponent::Default => Err(semantic_error(
+            ErrorCode::MalformedSyntaxTree,
+            span,
+            "Case outside of a switch statement",
+        )),
+        SyntaxComponent::ArrayLiteral => {
+            return Err(semantic_error(
+                ErrorCode::TypeMismatch,
+                span,
+                "Array literals can only initialise array declarations",
+            ))
         }
         SyntaxComponent::Declaration => {
             // advance iterator
-            let data_type = children
-                .next()
-                .ok_or(format!("Declaration must have a return type"))?;
+            let data_type = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Declaration must have a return type",
+            ))?;
 
-            let expected_value_type = match data_type.data() {
-                SyntaxComponent::Type(t) => match t {
-                    Type::Boolean => ReturnType::Boolean,
-                    Type::Number => ReturnType::Number,
-                },
-                _ => return Err(format!("Data type must be a type syntax component")),
+            let declared_type = match &data_type.data().component {
+                SyntaxComponent::Type(t) => t,
+                _ => {
+                    return Err(semantic_error(
+                        ErrorCode::MalformedSyntaxTree,
+                        span,
+                        "Data type must be a type syntax component",
+                    ))
+                }
             };
 
-            let identifier_data_type = match expected_value_type {
-                ReturnType::Number => DataType::Number,
-                ReturnType::Boolean => DataType::Boolean,
-                _ => panic!("Got expected void type"),
-            };
+            check_defined_type(data_type, symbol_table)?;
+            if let Type::Array(element_type, _) = declared_type {
+                if let Type::Struct(_) = element_type.as_ref() {
+                    return Err(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        data_type.data().span,
+                        "Arrays of structs are not supported",
+                    ));
+                }
+            }
 
-            let identifier = children
-                .next()
-                .ok_or(format!("Declaration must have an identifier to assign"))?;
+            // for arrays, the type of each element
+            let identifier_data_type = DataType::from(declared_type);
+            let expected_value_type = ReturnType::from(identifier_data_type.clone());
+
+            let identifier = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Declaration must have an identifier to assign",
+            ))?;
 
-            if let SyntaxComponent::Identifier(id_name) = identifier.data() {
+            if let SyntaxComponent::Identifier(id_name) = &identifier.data().component {
                 symbol_table.insert(
                     id_name.to_string(),
                     Symbol {
                         location: None,
                         data_type: identifier_data_type,
+                        length: declared_type.array_length(),
+                        span: identifier.data().span,
+                        constant: None,
                     },
                 );
             }
 
             let value = children.next();
+            if let (Some(length), Some(value)) = (declared_type.array_length(), value) {
+                if value.data().component != SyntaxComponent::ArrayLiteral {
+                    return Err(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        value.data().span,
+                        "Arrays must be initialised with an array literal, e.g. `[1, 2]`",
+                    ));
+                }
+
+                if value.iter().count() != length {
+                    return Err(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        value.data().span,
+                        format!("Expected {length} elements, got {}", value.iter().count()),
+                    ));
+                }
 
-            if value.is_some() {
-                let value_type = semantic_analysis(value.unwrap(), symbol_table)?;
-                if expected_value_type != value_type {
-                    return Err(format!(
-                        "Cannot define {:?} as {:?}",
-                        value_type, expected_value_type,
+                for element in value.iter() {
+                    let element_type = semantic_analysis(element, symbol_table)?;
+                    if !is_assignable(&expected_value_type, element, &element_type, symbol_table) {
+                        return Err(semantic_error(
+                            ErrorCode::TypeMismatch,
+                            element.data().span,
+                            format!(
+                                "Cannot store {} in an array of {}",
+                                element_type, expected_value_type
+                            ),
+                        ));
+                    }
+                }
+            } else if let Some(value) = value {
+                let value_type = semantic_analysis(value, symbol_table)?;
+                if !is_assignable(&expected_value_type, value, &value_type, symbol_table) {
+                    return Err(semantic_error(
+                        ErrorCode::TypeMismatch,
+                        span,
+                        format!("Cannot define {} as {}", value_type, expected_value_type),
                     ));
                 }
             }
 
             Ok(ReturnType::Void)
         }
+        SyntaxComponent::ConstantDeclaration => {
+            let type_node = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Constant declaration must have a type",
+            ))?;
+            let declared_type = type_node
+                .data()
+                .component
+                .try_get_type(Phase::Semantic)
+                .map_err(|diagnostic| diagnostic.with_span(span))?;
+
+            check_defined_type(type_node, symbol_table)?;
+            if !matches!(
+                declared_type,
+                Type::Integer | Type::Number | Type::Boolean | Type::Character | Type::Enum(_)
+            ) {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    type_node.data().span,
+                    format!(
+                        "Constants must be int, num, bool, char or enum values, got {}",
+                        declared_type
+                    ),
+                ));
+            }
+
+            let identifier = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Constant declaration must have an identifier",
+            ))?;
+            let name = identifier
+                .data()
+                .component
+                .try_get_identifier_name(Phase::Semantic)
+                .map_err(|diagnostic| diagnostic.with_span(span))?;
+
+            let value = children.next().ok_or(semantic_error(
+                ErrorCode::MalformedSyntaxTree,
+                span,
+                "Constant declaration must have a value",
+            ))?;
+            let data_type = DataType::from(&declared_type);
+            let expected_value_type = ReturnType::from(data_type.clone());
+            let value_type = semantic_analysis(value, symbol_table)?;
+            if !is_assignable(&expected_value_type, value, &value_type, symbol_table) {
+                return Err(semantic_error(
+                    ErrorCode::TypeMismatch,
+                    span,
+                    format!("Cannot define {} as {}", value_type, expected_value_type),
+                ));
+            }
+
+            let constant = symbol_table.evaluate_constant(value).map_err(|reason| {
+                semantic_error(
+                    ErrorCode::NonConstantValue,
+                    value.data().span,
+                    format!("The value of constant `{name}` must be known at compile time"),
+                )
+                .with_note(reason)
+            })?;
+
+            // declared after its value, so a constant cannot refer to itself
+            symbol_table.insert(
+                name,
+                Symbol {
+                    location: None,
+                    data_type,
+                    length: None,
+                    span: identifier.data().span,
+                    constant: Some(coerce_constant(constant, &declared_type)),
+                },
+            );
+
+            Ok(ReturnType::Void)
+        }
         SyntaxComponent::Type(_) => Ok(ReturnType::Void),
+        // already reported by the syntax analysis, only the declared name is kept
+        SyntaxComponent::Error(_) => {
+            if let Some(SyntaxComponent::Identifier(name)) = children
+                .next()
+                .map(|identifier| &identifier.data().component)
+            {
+                symbol_table.insert_broken_declaration(name.clone());
+            }
+            return Ok(ReturnType::Void);
+        }
     };
 
     if children.next().is_some() {
-        Err("Too many children".into())
+        Err(semantic_error(
+            ErrorCode::MalformedSyntaxTree,
+            span,
+            "Too many children",
+        ))
     } else {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexical_analysis::lexical_analysis, syntax_analysis::syntax_analysis};
+
+    fn analyse(source: &str) -> Result<ReturnType, Diagnostic> {
+        let (mut tokens, diagnostics) = lexical_analysis(source.chars());
+        assert_eq!(diagnostics, vec![]);
+        let (tree, diagnostics) = syntax_analysis(&mut tokens);
+        assert_eq!(diagnostics, vec![]);
+
+        semantic_analysis(&tree, &mut SymbolTable::new())
+    }
+
+    fn assert_error(source: &str, code: ErrorCode) -> Diagnostic {
+        let diagnostic = analyse(source).expect_err(source);
+        assert_eq!(diagnostic.code, code, "{source}: {diagnostic}");
+        diagnostic
+    }
+
+    #[test]
+    fn it_does_not_report_uses_of_broken_declarations() {
+        let sources = [
+            "num x = 1 +; print(x);",
+            "num a = 1 @ 2; print(a);",
+            "const int K = 5; num[K] xs; print(xs[4]);",
+        ];
+
+        for source in sources {
+            let (mut tokens, lexical_diagnostics) = lexical_analysis(source.chars());
+            let (tree, syntax_diagnostics) = syntax_analysis(&mut tokens);
+
+            // the broken declaration is the only error
+            assert_eq!(
+                lexical_diagnostics.len() + syntax_diagnostics.len(),
+                1,
+                "{source}"
+            );
+            assert!(
+                semantic_analysis(&tree, &mut SymbolTable::new()).is_ok(),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_still_reports_undeclared_identifiers_next_to_broken_declarations() {
+        let (mut tokens, _) = lexical_analysis("num x = 1 +; print(y);".chars());
+        let (tree, _) = syntax_analysis(&mut tokens);
+
+        let diagnostic = semantic_analysis(&tree, &mut SymbolTable::new()).unwrap_err();
+        assert_eq!(diagnostic.code, ErrorCode::UndeclaredIdentifier);
+    }
+
+    #[test]
+    fn it_rejects_changing_constants() {
+        for change in ["K = 2;", "K++;", "K += 2;", "--K;"] {
+            let diagnostic = assert_error(
+                &format!("const int K = 1; {change}"),
+                ErrorCode::InvalidAssignmentTarget,
+            );
+            assert_eq!(diagnostic.message, "Cannot change constant `K`");
+        }
+    }
+
+    #[test]
+    fn it_uses_ints_known_at_compile_time_as_nums() {
+        let source = "const int K = 4; const num N = 10 * K; num x = K - 5; num y = N / 2;";
+        assert!(analyse(source).is_ok());
+    }
+
+    #[test]
+    fn it_applies_the_same_rule_to_constants_and_variables() {
+        let source = "int i = 4;";
+        assert_error(&format!("{source} num x = i * 2;"), ErrorCode::TypeMismatch);
+        assert_error(
+            &format!("{source} const num N = i * 2;"),
+            ErrorCode::TypeMismatch,
+        );
+        assert_error(
+            &format!("{source} const int N = i * 2;"),
+            ErrorCode::NonConstantValue,
+        );
+        assert_error("const int K = 2.5;", ErrorCode::TypeMismatch);
+        assert_error("int k = 2.5;", ErrorCode::TypeMismatch);
+    }
+
+    #[test]
+    fn it_types_conditionals_against_the_expected_type() {
+        assert!(analyse("num x = 3.0; num sign = x < 0 ? 0 - 1 : 1;").is_ok());
+        assert!(analyse("num x = 3.0; num sign = x < 0 ? -1 : 1;").is_ok());
+        assert!(analyse("num x = 3.0; num y = x < 0 ? 0.5 : 0 - 1;").is_ok());
+        assert!(analyse("num x = 3.0; int sign = x < 0 ? 0 - 1 : 1;").is_ok());
+        assert_error(
+            "int i = 1; num x = 3.0; num y = x < 0 ? i : 1;",
+            ErrorCode::TypeMismatch,
+        );
+    }
+
+    #[test]
+    fn it_warns_about_non_exhaustive_switches_in_nested_blocks() {
+        let source = "enum Color { Red, Green, Blue }
+            fn paint(enum Color c) {
+                if (true) {
+                    switch (c) { case Color.Red: print(1); }
+                }
+            }";
+        let (mut tokens, _) = lexical_analysis(source.chars());
+        let (tree, _) = syntax_analysis(&mut tokens);
+        let mut symbol_table = SymbolTable::new();
+
+        assert!(semantic_analysis(&tree, &mut symbol_table).is_ok());
+
+        let warnings = symbol_table.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, ErrorCode::NonExhaustiveSwitch);
+        assert_eq!(
+            warnings[0].message,
+            "Switch on `Color` does not handle Green, Blue"
+        );
+    }
+
+    #[test]
+    fn it_rejects_duplicate_case_labels() {
+        let source = "const int K = 2; int i = 1;
+            switch (i) { case 1: print(1); case K, 1 + 1: print(2); }";
+        let diagnostic = assert_error(source, ErrorCode::DuplicateDefinition);
+        assert_eq!(diagnostic.message, "Duplicate case label");
+    }
+
+    #[test]
+    fn it_requires_a_return_on_every_path() {
+        let diagnostic = assert_error(
+            "fn sign(int n) -> int { if (n < 0) { return 0 - 1; } }",
+            ErrorCode::MissingReturn,
+        );
+        assert_eq!(
+            diagnostic.message,
+            "Function `sign` must return a value of type int on every path"
+        );
+
+        let source = "fn sign(int n) -> int { if (n < 0) { return 0 - 1; } else { return 1; } }";
+        assert!(analyse(source).is_ok());
+    }
+
+    #[test]
+    fn it_checks_the_number_of_arguments() {
+        let function = "fn add(int a, int b) -> int { return a + b; }";
+        for call in ["add(1);", "add(1, 2, 3);"] {
+            assert_error(
+                &format!("{function} {call}"),
+                ErrorCode::ArgumentCountMismatch,
+            );
+        }
+        assert!(analyse(&format!("{function} add(1, 2);")).is_ok());
+    }
+
+    #[test]
+    fn it_allows_recursion() {
+        let source = "fn fact(int n) -> int {
+                if (n <= 1) { return 1; }
+                return n * fact(n - 1);
+            }
+            print(fact(5));";
+        assert!(analyse(source).is_ok());
+    }
+
+    #[test]
+    fn it_allows_mutual_recursion() {
+        let source = "fn isEven(int n) -> bool {
+                if (n == 0) { return true; }
+                return isOdd(n - 1);
+            }
+            fn isOdd(int n) -> bool {
+                if (n == 0) { return false; }
+                return isEven(n - 1);
+            }
+            print(isEven(4));";
+        assert!(analyse(source).is_ok());
+    }
+
+    #[test]
+    fn it_rejects_returning_a_value_from_a_void_function() {
+        let diagnostic = assert_error("fn f() { return 1; }", ErrorCode::TypeMismatch);
+        assert_eq!(
+            diagnostic.message,
+            "Cannot return a value from a function without a return type"
+        );
+        assert_error("return 1;", ErrorCode::ReturnOutsideFunction);
+    }
+
+    #[test]
+    fn it_rejects_constant_indices_out_of_bounds() {
+        let array = "const int K = 3; num[3] xs;";
+        for index in ["3", "K", "0 - 1", "-1"] {
+            assert_error(
+                &format!("{array} print(xs[{index}]);"),
+                ErrorCode::IndexOutOfBounds,
+            );
+        }
+        assert!(analyse(&format!("{array} int i = 5; print(xs[K - 1], xs[i]);")).is_ok());
+    }
+
+    #[test]
+    fn it_rejects_jumps_outside_of_loops() {
+        for source in [
+            "break;",
+            "continue;",
+            "if (true) { break; }",
+            "fn f() { continue; } while (true) { f(); }",
+            "while (true) { } continue;",
+        ] {
+            assert_eq!(
+                analyse(source).map_err(|diagnostic| diagnostic.code),
+                Err(ErrorCode::JumpOutsideLoop),
+                "{source}"
+            );
+        }
+        assert!(analyse("int i = 0; while (true) { if (i > 3) { break; } i++; }").is_ok());
+    }
+
+    #[test]
+    fn it_allows_breaking_out_of_switches() {
+        assert!(analyse("int x = 1; switch (x) { case 1: print(1); break; }").is_ok());
+        assert!(analyse("int x = 1; while (true) { switch (x) { case 1: continue; } }").is_ok());
+
+        let diagnostic = assert_error(
+            "int x = 1; switch (x) { case 1: continue; }",
+            ErrorCode::JumpOutsideLoop,
+        );
+        assert_eq!(diagnostic.message, "Continue statement outside of a loop");
+    }
+
+    #[test]
+    fn it_uses_ints_known_at_compile_time_as_num_operands() {
+        let prelude = "const int K = 4; num x = 3.0;";
+        for expression in ["x * K", "x * (1 + 1)", "(K - 1) / x", "x > K * 2"] {
+            let declaration = if expression.contains('>') {
+                "bool"
+            } else {
+                "num"
+            };
+            let source = format!("{prelude} {declaration} y = {expression};");
+            assert!(analyse(&source).is_ok(), "{source}");
+        }
+
+        let diagnostic = assert_error(
+            &format!("{prelude} int i = 2; num y = x * i;"),
+            ErrorCode::TypeMismatch,
+        );
+        assert_eq!(
+            diagnostic.message,
+            "Cannot apply Multiply to num and int, convert one of them with int() or num()"
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::fmt;
+
+/**
+ * Location of a piece of source code
+ *
+ * `start` and `end` are byte offsets into the input (`end` is exclusive).
+ * `line` and `column` are 1-based and point at `start`.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Self {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// Smallest span covering both `self` and `other`
+    pub fn to(&self, other: Span) -> Span {
+        let first = if other.start < self.start {
+            other
+        } else {
+            *self
+        };
+
+        Span {
+            start: first.start,
+            end: self.end.max(other.end),
+            line: first.line,
+            column: first.column,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
@@ -1,8 +1,9 @@
 use std::iter::Peekable;
 
 use crate::{
-    syntax_analysis::{AbstractSyntaxTree, SyntaxComponent},
-    token::{expect_token, Token},
+    diagnostic::Diagnostic,
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent},
+    token::{expect_token, SpannedToken, Token},
 };
 
 use super::parse_statement;
@@ -18,22 +19,23 @@ use super::parse_statement;
  * }
  */
 pub fn parse_block(
-    input: &mut Peekable<impl Iterator<Item = Token>>,
-) -> Result<AbstractSyntaxTree, String> {
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
     log::trace!("Parsing block");
-    expect_token(&mut *input, Token::CurlyOpening)?;
+    let opening = expect_token(input, Token::CurlyOpening)?;
 
-    let mut node = AbstractSyntaxTree::new(SyntaxComponent::Sequence);
+    let mut node = new_node(SyntaxComponent::Sequence, opening.span);
 
     while let Some(token) = input.peek() {
-        if *token == Token::CurlyClosing {
+        if token.token == Token::CurlyClosing || token.token == Token::EOF {
             break;
         }
         let statement = parse_statement(input)?;
         node.push_back(statement);
     }
 
-    expect_token(input, Token::CurlyClosing)?;
+    let closing = expect_token(input, Token::CurlyClosing)?;
+    extend_span(&mut node, closing.span);
 
     Ok(node)
 }
@@ -1,49 +1,310 @@
 use std::iter::Peekable;
 
 use crate::{
+    diagnostic::{Diagnostic, ErrorCode, Phase},
     expression::parse_expression,
-    syntax_analysis::{AbstractSyntaxTree, SyntaxComponent, Type},
-    token::{expect_token, Token},
+    span::Span,
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent, Type},
+    token::{expect_token, invalid_token_error, SpannedToken, Token},
 };
 
-pub fn parse_declaration(
-    tokens: &mut Peekable<impl Iterator<Item = Token>>,
-) -> Result<AbstractSyntaxTree, String> {
-    let mut node = AbstractSyntaxTree::new(SyntaxComponent::Declaration);
+use super::synchronize;
+
+/**
+ * Error node replacing a declaration that failed to parse after its name was
+ * read. The name is kept, so that the statements using it are not reported
+ * as using an undeclared identifier.
+ */
+fn recover_declaration(
+    diagnostic: Diagnostic,
+    identifier: AbstractSyntaxTree,
+    tokens: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+    start: Span,
+) -> AbstractSyntaxTree {
+    log::debug!("Recovering from syntax error: {}", diagnostic);
+    let span = synchronize(tokens, start);
+    let mut node = new_node(SyntaxComponent::Error(diagnostic), span);
+    node.push_back(identifier);
+    node
+}
 
-    let type_token = tokens.next().unwrap();
+/// Parses what follows the name of a declaration, recovering from syntax errors in it
+fn parse_after_name<I: Iterator<Item = SpannedToken>>(
+    mut node: AbstractSyntaxTree,
+    tokens: &mut Peekable<I>,
+    parse: impl FnOnce(&mut AbstractSyntaxTree, &mut Peekable<I>) -> Result<(), Diagnostic>,
+) -> AbstractSyntaxTree {
+    match parse(&mut node, tokens) {
+        Ok(()) => node,
+        Err(diagnostic) => {
+            let identifier = node.iter().nth(1).unwrap().data();
+            let name = identifier
+                .component
+                .try_get_identifier_name(Phase::Syntax)
+                .unwrap();
+            let identifier = new_node(SyntaxComponent::Identifier(name), identifier.span);
+            recover_declaration(diagnostic, identifier, tokens, node.root().data().span)
+        }
+    }
+}
 
-    match type_token {
-        Token::NumType => {
-            node.push_back(AbstractSyntaxTree::new(SyntaxComponent::Type(Type::Number)));
+/**
+ * Parses a type into a type node. Structs and enums are named after their
+ * keyword, and types are optionally followed by an array length.
+ *
+ * Example:
+ * num[10]
+ * struct Point
+ * enum Color
+ */
+pub fn parse_type(
+    tokens: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    let type_token = match tokens.next() {
+        Some(type_token) => type_token,
+        None => {
+            return Err(Diagnostic::error(
+                Phase::Syntax,
+                ErrorCode::UnexpectedEndOfInput,
+                "Unexpected end of token stream, expected a type",
+            ))
         }
-        Token::BoolType => {
-            node.push_back(AbstractSyntaxTree::new(SyntaxComponent::Type(
-                Type::Boolean,
-            )));
+    };
+
+    let mut type_span = type_token.span;
+    let data_type = match type_token.token {
+        Token::Struct | Token::Enum => match tokens.next() {
+            Some(SpannedToken {
+                token: Token::Identifier(name),
+                span,
+            }) => {
+                type_span = type_span.to(span);
+                if type_token.token == Token::Struct {
+                    Type::Struct(name)
+                } else {
+                    Type::Enum(name)
+                }
+            }
+            Some(
+                error_token @ SpannedToken {
+                    token: Token::Error(_),
+                    ..
+                },
+            ) => return Err(invalid_token_error(&error_token)),
+            Some(SpannedToken { token, span }) => {
+                return Err(Diagnostic::error(
+                    Phase::Syntax,
+                    ErrorCode::ExpectedIdentifier,
+                    format!(
+                        "Expected a type name after {}, got {token}",
+                        type_token.token
+                    ),
+                )
+                .with_span(span))
+            }
+            None => {
+                return Err(Diagnostic::error(
+                    Phase::Syntax,
+                    ErrorCode::UnexpectedEndOfInput,
+                    format!("Expected a type name after {}", type_token.token),
+                ))
+            }
+        },
+        Token::NumType => Type::Number,
+        Token::IntType => Type::Integer,
+        Token::StringType => Type::String,
+        Token::CharType => Type::Character,
+        Token::BoolType => Type::Boolean,
+        Token::Error(_) => return Err(invalid_token_error(&type_token)),
+        token => {
+            return Err(Diagnostic::error(
+                Phase::Syntax,
+                ErrorCode::UnexpectedToken,
+                format!("Expected a type, got {token}"),
+            )
+            .with_span(type_token.span))
         }
-        token => panic!(
-            "First token of declaration must be a type token. Got {:?}",
-            token
-        ),
+    };
+
+    let mut node = new_node(SyntaxComponent::Type(data_type), type_span);
+
+    if tokens
+        .next_if(|token| token.token == Token::BracketOpening)
+        .is_some()
+    {
+        let length = match tokens.next() {
+            Some(SpannedToken {
+                token: Token::IntegerConstant(length),
+                ..
+            }) if length > 0 => length as usize,
+            Some(SpannedToken { token, span }) => {
+                // skip the rest of the length, so the declared name can still be read
+                if token != Token::BracketClosing {
+                    while tokens
+                        .next_if(|token| {
+                            !matches!(
+                                token.token,
+                                Token::BracketClosing | Token::Semicolon | Token::EOF
+                            )
+                        })
+                        .is_some()
+                    {}
+                    tokens.next_if(|token| token.token == Token::BracketClosing);
+                }
+
+                return Err(Diagnostic::error(
+                    Phase::Syntax,
+                    ErrorCode::UnexpectedToken,
+                    format!("Expected a positive int array length, got {token}"),
+                )
+                .with_span(span));
+            }
+            None => {
+                return Err(Diagnostic::error(
+                    Phase::Syntax,
+                    ErrorCode::UnexpectedEndOfInput,
+                    "Unexpected end of token stream, expected an array length",
+                ))
+            }
+        };
+        let closing = expect_token(tokens, Token::BracketClosing)?;
+
+        let element_type = node
+            .root()
+            .data()
+            .component
+            .try_get_type(Phase::Syntax)
+            .unwrap();
+        node = new_node(
+            SyntaxComponent::Type(Type::Array(Box::new(element_type), length)),
+            type_span.to(closing.span),
+        );
     }
 
-    if let Some(Token::Identifier(id)) = tokens.next() {
-        node.push_back(AbstractSyntaxTree::new(SyntaxComponent::Identifier(id)));
-    } else {
-        return Err("Expected identifier after type token".into());
+    Ok(node)
+}
+
+/// Parses a type followed by an identifier into a declaration node, as in `num foo`
+pub fn parse_typed_identifier(
+    tokens: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    let type_node = parse_type(tokens)?;
+    parse_identifier_with_type(type_node, tokens)
+}
+
+fn parse_identifier_with_type(
+    type_node: AbstractSyntaxTree,
+    tokens: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    let mut node = new_node(SyntaxComponent::Declaration, type_node.root().data().span);
+    node.push_back(type_node);
+
+    match tokens.next() {
+        Some(SpannedToken {
+            token: Token::Identifier(id),
+            span,
+        }) => {
+            node.push_back(new_node(SyntaxComponent::Identifier(id), span));
+            extend_span(&mut node, span);
+        }
+        Some(
+            error_token @ SpannedToken {
+                token: Token::Error(_),
+                ..
+            },
+        ) => return Err(invalid_token_error(&error_token)),
+        Some(SpannedToken { token, span }) => {
+            return Err(Diagnostic::error(
+                Phase::Syntax,
+                ErrorCode::ExpectedIdentifier,
+                format!("Expected identifier after type token, got {token}"),
+            )
+            .with_span(span))
+        }
+        None => {
+            return Err(Diagnostic::error(
+                Phase::Syntax,
+                ErrorCode::UnexpectedEndOfInput,
+                "Expected identifier after type token",
+            ))
+        }
     }
 
-    if let Token::OperatorAssignment = tokens
-        .peek()
-        .ok_or("Unexpected end of token stream while parsing declaration")?
-    {
-        // skip assignment token
-        tokens.next();
-        node.push_back(parse_expression(tokens)?);
+    Ok(node)
+}
+
+pub fn parse_declaration(
+    tokens: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    let start = tokens.peek().map(|token| token.span).unwrap_or_default();
+    let type_node = match parse_type(tokens) {
+        Ok(type_node) => type_node,
+        // the name following a broken type is still declared
+        Err(diagnostic) => match tokens.peek() {
+            Some(SpannedToken {
+                token: Token::Identifier(name),
+                span,
+            }) => {
+                let identifier = new_node(SyntaxComponent::Identifier(name.clone()), *span);
+                tokens.next();
+                return Ok(recover_declaration(diagnostic, identifier, tokens, start));
+            }
+            _ => return Err(diagnostic),
+        },
     };
+    parse_declaration_with_type(type_node, tokens)
+}
 
-    expect_token(tokens, Token::Semicolon)?;
+/**
+ * constant declaration, the value must be known at compile time
+ * const <type> <identifier> = <expr>;
+ *
+ * Example:
+ * const num N = 10 * 4;
+ */
+pub fn parse_constant_declaration(
+    tokens: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    let keyword = expect_token(tokens, Token::Const)?;
+    let type_node = parse_type(tokens)?;
+    let mut node = parse_identifier_with_type(type_node, tokens)?;
+    node.root_mut().data_mut().component = SyntaxComponent::ConstantDeclaration;
+    extend_span(&mut node, keyword.span);
 
-    Ok(node)
+    Ok(parse_after_name(node, tokens, |node, tokens| {
+        expect_token(tokens, Token::OperatorAssignment)?;
+        node.push_back(parse_expression(tokens)?);
+
+        let semicolon = expect_token(tokens, Token::Semicolon)?;
+        extend_span(node, semicolon.span);
+        Ok(())
+    }))
+}
+
+/// Parses the rest of a declaration whose type was already parsed
+pub fn parse_declaration_with_type(
+    type_node: AbstractSyntaxTree,
+    tokens: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    let node = parse_identifier_with_type(type_node, tokens)?;
+
+    Ok(parse_after_name(node, tokens, |node, tokens| {
+        if let Token::OperatorAssignment = tokens
+            .peek()
+            .ok_or(Diagnostic::error(
+                Phase::Syntax,
+                ErrorCode::UnexpectedEndOfInput,
+                "Unexpected end of token stream while parsing declaration",
+            ))?
+            .token
+        {
+            // skip assignment token
+            tokens.next();
+            node.push_back(parse_expression(tokens)?);
+        };
+
+        let semicolon = expect_token(tokens, Token::Semicolon)?;
+        extend_span(node, semicolon.span);
+        Ok(())
+    }))
 }
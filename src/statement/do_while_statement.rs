@@ -0,0 +1,40 @@
+use std::iter::Peekable;
+
+use crate::{
+    diagnostic::Diagnostic,
+    expression::parse_expression,
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent},
+    token::{expect_token, SpannedToken, Token},
+};
+
+use super::parse_statement;
+
+/**
+ * do while statement, the body runs at least once
+ * do <stmt> while (<expr>);
+ *
+ * Example:
+ * do i++; while (i < 100);
+ */
+pub fn parse_do_while(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    log::trace!("Parsing Do While");
+    let keyword = expect_token(input, Token::Do)?;
+    let mut node = new_node(SyntaxComponent::DoWhile, keyword.span);
+
+    // loop body
+    node.push_back(parse_statement(&mut *input)?);
+
+    expect_token(input, Token::While)?;
+    expect_token(input, Token::ParenthesisOpening)?;
+
+    // condition
+    node.push_back(parse_expression(&mut *input)?);
+
+    expect_token(input, Token::ParenthesisClosing)?;
+    let semicolon = expect_token(input, Token::Semicolon)?;
+    extend_span(&mut node, semicolon.span);
+
+    Ok(node)
+}
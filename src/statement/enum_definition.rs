@@ -0,0 +1,102 @@
+use std::iter::Peekable;
+
+use crate::{
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent, Type},
+    token::{expect_token, invalid_token_error, SpannedToken, Token},
+};
+
+use super::{parse_declaration_with_type, parse_type};
+
+/**
+ * enum definition, only allowed at the top level of a program, or
+ * declaration of an enum variable
+ * enum <identifier> { <identifier>, ... }
+ * enum <identifier> <identifier> = <expr>;
+ *
+ * Variants are separated by commas, a trailing comma is allowed.
+ *
+ * Example:
+ * enum Color { Red, Green, Blue }
+ * enum Color background = Color.Red;
+ */
+pub fn parse_enum(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    log::trace!("Parsing enum");
+    let type_node = parse_type(input)?;
+
+    if input.peek().map(|token| &token.token) != Some(&Token::CurlyOpening) {
+        return parse_declaration_with_type(type_node, input);
+    }
+
+    let span = type_node.root().data().span;
+    let Ok(Type::Enum(name)) = type_node
+        .root()
+        .data()
+        .component
+        .try_get_type(Phase::Syntax)
+    else {
+        return Err(Diagnostic::error(
+            Phase::Syntax,
+            ErrorCode::UnexpectedToken,
+            "Expected an enum name before the variants of an enum",
+        )
+        .with_span(span));
+    };
+
+    let mut node = new_node(SyntaxComponent::Enum(name), span);
+
+    expect_token(input, Token::CurlyOpening)?;
+    loop {
+        match input.next() {
+            Some(SpannedToken {
+                token: Token::Identifier(variant),
+                span,
+            }) => node.push_back(new_node(SyntaxComponent::Identifier(variant), span)),
+            // only reachable right after the opening brace, trailing commas end the loop
+            Some(SpannedToken {
+                token: Token::CurlyClosing,
+                span,
+            }) => {
+                return Err(Diagnostic::error(
+                    Phase::Syntax,
+                    ErrorCode::ExpectedIdentifier,
+                    "Expected at least one variant in enum",
+                )
+                .with_span(span))
+            }
+            Some(
+                error_token @ SpannedToken {
+                    token: Token::Error(_),
+                    ..
+                },
+            ) => return Err(invalid_token_error(&error_token)),
+            Some(SpannedToken { token, span }) => {
+                return Err(Diagnostic::error(
+                    Phase::Syntax,
+                    ErrorCode::ExpectedIdentifier,
+                    format!("Expected a variant name, got {token}"),
+                )
+                .with_span(span))
+            }
+            None => {
+                return Err(Diagnostic::error(
+                    Phase::Syntax,
+                    ErrorCode::UnexpectedEndOfInput,
+                    "Unexpected end of token stream, expected a variant name",
+                ))
+            }
+        }
+
+        if input.next_if(|token| token.token == Token::Comma).is_none()
+            || input.peek().map(|token| &token.token) == Some(&Token::CurlyClosing)
+        {
+            break;
+        }
+    }
+    let closing = expect_token(input, Token::CurlyClosing)?;
+    extend_span(&mut node, closing.span);
+
+    Ok(node)
+}
@@ -1,9 +1,10 @@
 use std::iter::Peekable;
 
 use crate::{
+    diagnostic::Diagnostic,
     statement::{parse_optional_expression, parse_statement},
-    syntax_analysis::{AbstractSyntaxTree, SyntaxComponent},
-    token::{expect_token, Token},
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent},
+    token::{expect_token, SpannedToken, Token},
 };
 
 /**
@@ -14,36 +15,39 @@ use crate::{
  * for (i = 0; i < 100; i++) do_something();
  */
 pub fn parse_for(
-    input: &mut Peekable<impl Iterator<Item = Token>>,
-) -> Result<AbstractSyntaxTree, String> {
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
     log::trace!("Parsing For");
-    let mut node = AbstractSyntaxTree::new(SyntaxComponent::For);
+    let keyword = expect_token(input, Token::For)?;
 
-    expect_token(&mut *input, Token::For)?;
+    let mut node = new_node(SyntaxComponent::For, keyword.span);
 
     // (
-    expect_token(&mut *input, Token::ParenthesisOpening)?;
+    expect_token(input, Token::ParenthesisOpening)?;
 
     // initial expression. e.g: i = 0
     node.push_back(parse_optional_expression(&mut *input)?);
 
     // ;
-    expect_token(&mut *input, Token::Semicolon)?;
+    expect_token(input, Token::Semicolon)?;
 
     // condition. e.g: i < 100
     node.push_back(parse_optional_expression(&mut *input)?);
 
     // ;
-    expect_token(&mut *input, Token::Semicolon)?;
+    expect_token(input, Token::Semicolon)?;
 
     // post loop expression. e.g. i++
     node.push_back(parse_optional_expression(&mut *input)?);
 
     // )
-    expect_token(&mut *input, Token::ParenthesisClosing)?;
+    expect_token(input, Token::ParenthesisClosing)?;
 
     // loop body
-    node.push_back(parse_statement(&mut *input)?);
+    let body = parse_statement(&mut *input)?;
+    let body_span = body.root().data().span;
+    node.push_back(body);
+    extend_span(&mut node, body_span);
 
     Ok(node)
 }
@@ -0,0 +1,86 @@
+use std::iter::Peekable;
+
+use crate::{
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent},
+    token::{expect_token, invalid_token_error, SpannedToken, Token},
+};
+
+use super::{parse_block, parse_type, parse_typed_identifier};
+
+/**
+ * function definition, only allowed at the top level of a program
+ * fn <identifier>(<type> <identifier>, ...) -> <type> <block>
+ *
+ * The return type is left out for functions without a result.
+ *
+ * Example:
+ * fn max(num a, num b) -> num {
+ *      if (a > b) return a;
+ *      return b;
+ * }
+ */
+pub fn parse_function(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    log::trace!("Parsing function definition");
+    let keyword = expect_token(input, Token::Fn)?;
+
+    let name = match input.peek() {
+        Some(SpannedToken {
+            token: Token::Identifier(name),
+            ..
+        }) => name.clone(),
+        Some(
+            error_token @ SpannedToken {
+                token: Token::Error(_),
+                ..
+            },
+        ) => return Err(invalid_token_error(error_token)),
+        Some(SpannedToken { token, span }) => {
+            return Err(Diagnostic::error(
+                Phase::Syntax,
+                ErrorCode::ExpectedIdentifier,
+                format!("Expected function name after fn, got {token}"),
+            )
+            .with_span(*span))
+        }
+        None => {
+            return Err(Diagnostic::error(
+                Phase::Syntax,
+                ErrorCode::UnexpectedEndOfInput,
+                "Expected function name after fn",
+            ))
+        }
+    };
+    input.next();
+
+    let mut node = new_node(SyntaxComponent::Function(name), keyword.span);
+
+    let opening = expect_token(input, Token::ParenthesisOpening)?;
+    let mut parameters = new_node(SyntaxComponent::Parameters, opening.span);
+    if input.peek().map(|token| &token.token) != Some(&Token::ParenthesisClosing) {
+        loop {
+            parameters.push_back(parse_typed_identifier(input)?);
+
+            if input.next_if(|token| token.token == Token::Comma).is_none() {
+                break;
+            }
+        }
+    }
+    let closing = expect_token(input, Token::ParenthesisClosing)?;
+    extend_span(&mut parameters, closing.span);
+    node.push_back(parameters);
+
+    match input.next_if(|token| token.token == Token::Arrow) {
+        Some(_) => node.push_back(parse_type(input)?),
+        None => node.push_back(new_node(SyntaxComponent::Null, closing.span)),
+    }
+
+    let body = parse_block(input)?;
+    let body_span = body.root().data().span;
+    node.push_back(body);
+    extend_span(&mut node, body_span);
+
+    Ok(node)
+}
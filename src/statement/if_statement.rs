@@ -1,9 +1,10 @@
 use std::iter::Peekable;
 
 use crate::{
+    diagnostic::Diagnostic,
     expression::parse_expression,
-    syntax_analysis::{AbstractSyntaxTree, SyntaxComponent},
-    token::{expect_token, Token},
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent},
+    token::{expect_token, SpannedToken, Token},
 };
 
 use super::parse_statement;
@@ -11,26 +12,41 @@ use super::parse_statement;
 /**
  * if statement
  * if (<expr>) <stmt>
+ * if (<expr>) <stmt> else <stmt>
+ *
+ * `else if` chains are else branches holding another if statement. An else
+ * belongs to the closest if without one.
  *
  * Example:
- * if (i < 5) i = i + 5;
+ * if (i < 5) i = i + 5; else if (i < 10) i = 10; else i = 0;
  */
 pub fn parse_if(
-    input: &mut Peekable<impl Iterator<Item = Token>>,
-) -> Result<AbstractSyntaxTree, String> {
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
     log::trace!("Parsing If");
-    expect_token(&mut *input, Token::If)?;
-    let mut node = AbstractSyntaxTree::new(SyntaxComponent::If);
+    let keyword = expect_token(input, Token::If)?;
+    let mut node = new_node(SyntaxComponent::If, keyword.span);
 
-    expect_token(&mut *input, Token::ParenthesisOpening)?;
+    expect_token(input, Token::ParenthesisOpening)?;
 
     // condition
     node.push_back(parse_expression(&mut *input)?);
 
-    expect_token(&mut *input, Token::ParenthesisClosing)?;
+    expect_token(input, Token::ParenthesisClosing)?;
 
     // body
-    node.push_back(parse_statement(&mut *input)?);
+    let body = parse_statement(&mut *input)?;
+    let body_span = body.root().data().span;
+    node.push_back(body);
+    extend_span(&mut node, body_span);
+
+    // optional else branch
+    if input.next_if(|token| token.token == Token::Else).is_some() {
+        let else_body = parse_statement(&mut *input)?;
+        let else_body_span = else_body.root().data().span;
+        node.push_back(else_body);
+        extend_span(&mut node, else_body_span);
+    }
 
     Ok(node)
 }
@@ -0,0 +1,35 @@
+use std::iter::Peekable;
+
+use crate::{
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree},
+    token::{expect_token, SpannedToken, Token},
+};
+
+/**
+ * jump statement, leaves the innermost loop or skips to its next iteration
+ * break;
+ * continue;
+ *
+ * Example:
+ * for (i = 0; i < 100; i++) if (i == 50) break;
+ */
+pub fn parse_jump(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    log::trace!("Parsing jump");
+    let keyword = input
+        .next_if(|token| matches!(token.token, Token::Break | Token::Continue))
+        .ok_or(Diagnostic::error(
+            Phase::Syntax,
+            ErrorCode::UnexpectedToken,
+            "Expected break or continue",
+        ))?;
+
+    let mut node = new_node(keyword.token.try_into()?, keyword.span);
+
+    let semicolon = expect_token(input, Token::Semicolon)?;
+    extend_span(&mut node, semicolon.span);
+
+    Ok(node)
+}
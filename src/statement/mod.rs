@@ -1,13 +1,32 @@
 mod block_statement;
 mod declaration;
+mod do_while_statement;
+mod enum_definition;
 mod for_statement;
+mod function_definition;
 mod if_statement;
+mod jump_statement;
 mod optional_expression_statement;
+mod print_statement;
+mod return_statement;
+#[allow(clippy::module_inception)]
 mod statement;
+mod struct_definition;
+mod switch_statement;
+mod while_statement;
 
 pub use block_statement::*;
 pub use declaration::*;
+pub use do_while_statement::*;
+pub use enum_definition::*;
 pub use for_statement::*;
+pub use function_definition::*;
 pub use if_statement::*;
+pub use jump_statement::*;
 pub use optional_expression_statement::*;
+pub use print_statement::*;
+pub use return_statement::*;
 pub use statement::*;
+pub use struct_definition::*;
+pub use switch_statement::*;
+pub use while_statement::*;
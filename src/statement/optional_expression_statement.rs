@@ -1,31 +1,32 @@
 use std::iter::Peekable;
 
 use crate::{
+    diagnostic::Diagnostic,
     expression::parse_expression,
-    syntax_analysis::{AbstractSyntaxTree, SyntaxComponent},
-    token::Token,
+    syntax_analysis::{new_node, AbstractSyntaxTree, SyntaxComponent},
+    token::{SpannedToken, Token},
 };
 
 pub fn parse_optional_expression(
-    input: &mut Peekable<impl Iterator<Item = Token>>,
-) -> Result<AbstractSyntaxTree, String> {
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
     log::trace!("Parsing Optional Expression");
 
     let ok_value = match input.peek() {
-        Some(next_token) => match next_token {
+        Some(next_token) => match next_token.token {
             // empty expression
             Token::EOF | Token::Semicolon | Token::ParenthesisClosing => {
                 log::trace!("Got to end of expression");
-                AbstractSyntaxTree::new(SyntaxComponent::Null)
-            }
-            _ => {
-                let parsed_expression = parse_expression(input)?;
-                parsed_expression
+                // empty span right before the token that ends the expression
+                let mut span = next_token.span;
+                span.end = span.start;
+                new_node(SyntaxComponent::Null, span)
             }
+            _ => parse_expression(input)?,
         },
         None => {
             log::trace!("Got to end of token iterator while parsing optional expression");
-            AbstractSyntaxTree::new(SyntaxComponent::Null)
+            AbstractSyntaxTree::new(SyntaxComponent::Null.into())
         }
     };
 
@@ -0,0 +1,38 @@
+use std::iter::Peekable;
+
+use crate::{
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    expression::parse_arguments,
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent},
+    token::{expect_token, SpannedToken, Token},
+};
+
+/**
+ * print statement, writes its arguments one after the other and ends the line
+ * print(expression, ...);
+ * print();
+ *
+ * Example:
+ * print("total: ", total);
+ */
+pub fn parse_print(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    log::trace!("Parsing print");
+    let keyword = input
+        .next_if(|token| token.token == Token::Print)
+        .ok_or(Diagnostic::error(
+            Phase::Syntax,
+            ErrorCode::UnexpectedToken,
+            "Expected print",
+        ))?;
+
+    let mut node = new_node(SyntaxComponent::Print, keyword.span);
+
+    parse_arguments(input, &mut node)?;
+
+    let semicolon = expect_token(input, Token::Semicolon)?;
+    extend_span(&mut node, semicolon.span);
+
+    Ok(node)
+}
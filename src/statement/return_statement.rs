@@ -0,0 +1,34 @@
+use std::iter::Peekable;
+
+use crate::{
+    diagnostic::Diagnostic,
+    expression::parse_expression,
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent},
+    token::{expect_token, SpannedToken, Token},
+};
+
+/**
+ * return statement, leaves the enclosing function
+ * return <expression>;
+ * return;
+ *
+ * Example:
+ * return n * factorial(n - 1);
+ */
+pub fn parse_return(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    log::trace!("Parsing return");
+    let keyword = expect_token(input, Token::Return)?;
+
+    let mut node = new_node(SyntaxComponent::Return, keyword.span);
+
+    if input.peek().map(|token| &token.token) != Some(&Token::Semicolon) {
+        node.push_back(parse_expression(input)?);
+    }
+
+    let semicolon = expect_token(input, Token::Semicolon)?;
+    extend_span(&mut node, semicolon.span);
+
+    Ok(node)
+}
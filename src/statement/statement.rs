@@ -1,28 +1,229 @@
 use std::iter::Peekable;
 
 use crate::{
-    syntax_analysis::AbstractSyntaxTree,
-    token::{expect_token, Token},
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    span::Span,
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent},
+    token::{expect_token, SpannedToken, Token},
 };
 
-use super::{parse_block, parse_declaration, parse_for, parse_if, parse_optional_expression};
+use super::{
+    parse_block, parse_constant_declaration, parse_declaration, parse_do_while, parse_enum,
+    parse_for, parse_function, parse_if, parse_jump, parse_optional_expression, parse_print,
+    parse_return, parse_struct, parse_switch, parse_while,
+};
+
+// tokens a statement can begin or end with, where parsing resumes after an error
+fn is_synchronization_point(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::CurlyOpening
+            | Token::CurlyClosing
+            | Token::If
+            | Token::Else
+            | Token::For
+            | Token::While
+            | Token::Do
+            | Token::Fn
+            | Token::Struct
+            | Token::Enum
+            | Token::Const
+            | Token::Switch
+            | Token::Case
+            | Token::Default
+            | Token::Return
+            | Token::Print
+            | Token::NumType
+            | Token::IntType
+            | Token::StringType
+            | Token::CharType
+            | Token::BoolType
+            | Token::EOF
+    )
+}
+
+/**
+ * Skips tokens up to the end of the broken statement, i.e. past the next `;`
+ * or right before the next synchronization point.
+ *
+ * Returns the span of the broken statement
+ */
+pub(super) fn synchronize(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+    start: Span,
+) -> Span {
+    let mut span = start;
+
+    // make sure at least one token is consumed, so parsing always moves forward
+    if let Some(token) =
+        input.next_if(|token| token.span.start == start.start && token.token != Token::EOF)
+    {
+        span = span.to(token.span);
+        if token.token == Token::Semicolon {
+            return span;
+        }
+    }
+
+    while let Some(token) = input.peek() {
+        if is_synchronization_point(&token.token) {
+            break;
+        }
 
+        let token = input.next().unwrap();
+        span = span.to(token.span);
+
+        if token.token == Token::Semicolon {
+            break;
+        }
+    }
+
+    span
+}
+
+/**
+ * Parses a statement, recovering from syntax errors.
+ *
+ * Statements that fail to parse are replaced by an error node holding the
+ * diagnostic, so the statements following it are parsed as usual.
+ */
 pub fn parse_statement(
-    input: &mut Peekable<impl Iterator<Item = Token>>,
-) -> Result<AbstractSyntaxTree, String> {
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    parse_with_recovery(input, parse_statement_without_recovery)
+}
+
+/// Parses a statement of the top level of a program, the only place functions, structs and enums can be defined
+pub fn parse_top_level_statement(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    parse_with_recovery(input, |input| match input.peek() {
+        Some(SpannedToken {
+            token: Token::Fn, ..
+        }) => parse_function(input),
+        Some(SpannedToken {
+            token: Token::Struct,
+            ..
+        }) => parse_struct(input),
+        Some(SpannedToken {
+            token: Token::Enum, ..
+        }) => parse_enum(input),
+        _ => parse_statement_without_recovery(input),
+    })
+}
+
+fn parse_with_recovery<I: Iterator<Item = SpannedToken>>(
+    input: &mut Peekable<I>,
+    parse: impl FnOnce(&mut Peekable<I>) -> Result<AbstractSyntaxTree, Diagnostic>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    let start = match input.peek() {
+        Some(next_token) => next_token.span,
+        None => {
+            return Err(Diagnostic::error(
+                Phase::Syntax,
+                ErrorCode::UnexpectedEndOfInput,
+                "Unexpected end of input while parsing statement",
+            ))
+        }
+    };
+
+    match parse(input) {
+        Ok(statement) => Ok(statement),
+        Err(diagnostic) => {
+            log::debug!("Recovering from syntax error: {}", diagnostic);
+            let span = synchronize(input, start);
+            Ok(new_node(SyntaxComponent::Error(diagnostic), span))
+        }
+    }
+}
+
+fn parse_statement_without_recovery(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
     let value = match input.peek() {
-        Some(next_token) => match next_token {
-            Token::BoolType | Token::NumType => parse_declaration(input)?,
+        Some(next_token) => match next_token.token {
+            Token::BoolType
+            | Token::NumType
+            | Token::IntType
+            | Token::StringType
+            | Token::CharType => parse_declaration(input)?,
+            Token::Const => parse_constant_declaration(input)?,
             Token::For => parse_for(input)?,
+            Token::While => parse_while(input)?,
+            Token::Do => parse_do_while(input)?,
+            Token::Break | Token::Continue => parse_jump(input)?,
+            Token::Print => parse_print(input)?,
+            Token::Return => parse_return(input)?,
+            Token::Fn => {
+                return Err(Diagnostic::error(
+                    Phase::Syntax,
+                    ErrorCode::UnexpectedToken,
+                    "Functions can only be defined at the top level of a program",
+                )
+                .with_span(next_token.span))
+            }
+            Token::Struct | Token::Enum => {
+                let statement = if next_token.token == Token::Struct {
+                    parse_struct(input)?
+                } else {
+                    parse_enum(input)?
+                };
+
+                // the definition was parsed completely, so there is nothing to skip
+                let kind = match statement.root().data().component {
+                    SyntaxComponent::Struct(_) => Some("Structs"),
+                    SyntaxComponent::Enum(_) => Some("Enums"),
+                    _ => None,
+                };
+                match kind {
+                    Some(kind) => {
+                        let span = statement.root().data().span;
+                        let diagnostic = Diagnostic::error(
+                            Phase::Syntax,
+                            ErrorCode::UnexpectedToken,
+                            format!("{kind} can only be defined at the top level of a program"),
+                        )
+                        .with_span(span);
+                        new_node(SyntaxComponent::Error(diagnostic), span)
+                    }
+                    None => statement,
+                }
+            }
             Token::If => parse_if(input)?,
+            Token::Switch => parse_switch(input)?,
+            Token::Case | Token::Default => {
+                return Err(Diagnostic::error(
+                    Phase::Syntax,
+                    ErrorCode::UnexpectedToken,
+                    format!(
+                        "Unexpected {} outside of a switch statement",
+                        next_token.token
+                    ),
+                )
+                .with_span(next_token.span))
+            }
+            Token::Else => {
+                return Err(Diagnostic::error(
+                    Phase::Syntax,
+                    ErrorCode::UnexpectedToken,
+                    "Unexpected else without a preceding if statement",
+                )
+                .with_span(next_token.span))
+            }
             Token::CurlyOpening => parse_block(input)?,
             _ => {
-                let optional_expression = parse_optional_expression(input)?;
-                expect_token(input, Token::Semicolon)?;
+                let mut optional_expression = parse_optional_expression(input)?;
+                let semicolon = expect_token(input, Token::Semicolon)?;
+                extend_span(&mut optional_expression, semicolon.span);
                 optional_expression
             }
         },
-        None => return Err("Unexpected end of input while parsing statement".into()),
+        None => {
+            return Err(Diagnostic::error(
+                Phase::Syntax,
+                ErrorCode::UnexpectedEndOfInput,
+                "Unexpected end of input while parsing statement",
+            ))
+        }
     };
 
     Ok(value)
@@ -0,0 +1,65 @@
+use std::iter::Peekable;
+
+use crate::{
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent, Type},
+    token::{expect_token, SpannedToken, Token},
+};
+
+use super::{parse_declaration_with_type, parse_type, parse_typed_identifier};
+
+/**
+ * struct definition, only allowed at the top level of a program, or
+ * declaration of a struct variable
+ * struct <identifier> { <type> <identifier>; ... }
+ * struct <identifier> <identifier>;
+ *
+ * Example:
+ * struct Point {
+ *      num x;
+ *      num y;
+ * }
+ * struct Point origin;
+ */
+pub fn parse_struct(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    log::trace!("Parsing struct");
+    let type_node = parse_type(input)?;
+
+    if input.peek().map(|token| &token.token) != Some(&Token::CurlyOpening) {
+        return parse_declaration_with_type(type_node, input);
+    }
+
+    let span = type_node.root().data().span;
+    let Ok(Type::Struct(name)) = type_node
+        .root()
+        .data()
+        .component
+        .try_get_type(Phase::Syntax)
+    else {
+        return Err(Diagnostic::error(
+            Phase::Syntax,
+            ErrorCode::UnexpectedToken,
+            "Expected a struct name before the fields of a struct",
+        )
+        .with_span(span));
+    };
+
+    let mut node = new_node(SyntaxComponent::Struct(name), span);
+
+    expect_token(input, Token::CurlyOpening)?;
+    while input
+        .peek()
+        .is_some_and(|token| token.token != Token::CurlyClosing && token.token != Token::EOF)
+    {
+        let mut field = parse_typed_identifier(input)?;
+        let semicolon = expect_token(input, Token::Semicolon)?;
+        extend_span(&mut field, semicolon.span);
+        node.push_back(field);
+    }
+    let closing = expect_token(input, Token::CurlyClosing)?;
+    extend_span(&mut node, closing.span);
+
+    Ok(node)
+}
@@ -0,0 +1,93 @@
+use std::iter::Peekable;
+
+use crate::{
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    expression::parse_expression,
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent},
+    token::{expect_token, SpannedToken, Token},
+};
+
+use super::parse_statement;
+
+/**
+ * switch statement
+ * switch (<expr>) { case <expr>, ...: <stmt>... default: <stmt>... }
+ *
+ * Only the body of the matching case runs, there is no fallthrough, and
+ * `break` leaves the switch early. A case can list several labels, and at
+ * most one default case is allowed.
+ *
+ * Example:
+ * switch (c) {
+ *      case Color.Red: print("red");
+ *      case Color.Green, Color.Blue: print("not red");
+ * }
+ */
+pub fn parse_switch(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    log::trace!("Parsing switch");
+    let keyword = expect_token(input, Token::Switch)?;
+    let mut node = new_node(SyntaxComponent::Switch, keyword.span);
+
+    expect_token(input, Token::ParenthesisOpening)?;
+
+    // value switched on
+    node.push_back(parse_expression(&mut *input)?);
+
+    expect_token(input, Token::ParenthesisClosing)?;
+    expect_token(input, Token::CurlyOpening)?;
+
+    let mut has_default = false;
+    while let Some(token) =
+        input.next_if(|token| matches!(token.token, Token::Case | Token::Default))
+    {
+        let mut case = if token.token == Token::Case {
+            let mut case = new_node(SyntaxComponent::Case, token.span);
+            loop {
+                case.push_back(parse_expression(&mut *input)?);
+
+                if input.next_if(|token| token.token == Token::Comma).is_none() {
+                    break;
+                }
+            }
+            case
+        } else if has_default {
+            return Err(Diagnostic::error(
+                Phase::Syntax,
+                ErrorCode::UnexpectedToken,
+                "Switch statements can only have one default case",
+            )
+            .with_span(token.span));
+        } else {
+            has_default = true;
+            new_node(SyntaxComponent::Default, token.span)
+        };
+
+        let colon = expect_token(input, Token::Colon)?;
+
+        // body, up to the next case
+        let mut body = new_node(SyntaxComponent::Sequence, colon.span);
+        while input.peek().is_some_and(|token| {
+            !matches!(
+                token.token,
+                Token::Case | Token::Default | Token::CurlyClosing | Token::EOF
+            )
+        }) {
+            let statement = parse_statement(&mut *input)?;
+            let statement_span = statement.root().data().span;
+            body.push_back(statement);
+            extend_span(&mut body, statement_span);
+        }
+
+        let body_span = body.root().data().span;
+        case.push_back(body);
+        extend_span(&mut case, body_span);
+        node.push_back(case);
+    }
+
+    let closing = expect_token(input, Token::CurlyClosing)?;
+    extend_span(&mut node, closing.span);
+
+    Ok(node)
+}
@@ -0,0 +1,40 @@
+use std::iter::Peekable;
+
+use crate::{
+    diagnostic::Diagnostic,
+    expression::parse_expression,
+    syntax_analysis::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent},
+    token::{expect_token, SpannedToken, Token},
+};
+
+use super::parse_statement;
+
+/**
+ * while statement
+ * while (<expr>) <stmt>
+ *
+ * Example:
+ * while (i < 100) i++;
+ */
+pub fn parse_while(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> Result<AbstractSyntaxTree, Diagnostic> {
+    log::trace!("Parsing While");
+    let keyword = expect_token(input, Token::While)?;
+    let mut node = new_node(SyntaxComponent::While, keyword.span);
+
+    expect_token(input, Token::ParenthesisOpening)?;
+
+    // condition
+    node.push_back(parse_expression(&mut *input)?);
+
+    expect_token(input, Token::ParenthesisClosing)?;
+
+    // loop body
+    let body = parse_statement(&mut *input)?;
+    let body_span = body.root().data().span;
+    node.push_back(body);
+    extend_span(&mut node, body_span);
+
+    Ok(node)
+}
@@ -0,0 +1,340 @@
+use std::cmp::Ordering;
+
+use trees::Node;
+
+use crate::{
+    code_generation::Value,
+    syntax_analysis::{
+        BinaryOperation, Constant, LogicalOperation, Relation, SyntaxComponent, SyntaxNode, Type,
+        UnaryOperation,
+    },
+};
+
+use super::SymbolTable;
+
+fn overflow() -> String {
+    "Overflow while evaluating a constant".to_string()
+}
+
+/// Integer literals next to a num are nums as well
+fn promote(left: Value, right: Value) -> (Value, Value) {
+    match (left, right) {
+        (Value::Integer(left), Value::Float(right)) => {
+            (Value::Float(left as f64), Value::Float(right))
+        }
+        (Value::Float(left), Value::Integer(right)) => {
+            (Value::Float(left), Value::Float(right as f64))
+        }
+        operands => operands,
+    }
+}
+
+fn evaluate_integer_operation(
+    operation: &BinaryOperation,
+    left: i64,
+    right: i64,
+) -> Result<Value, String> {
+    let result = match operation {
+        BinaryOperation::Divide | BinaryOperation::IntegerDivide | BinaryOperation::Modulo
+            if right == 0 =>
+        {
+            return Err("Division by zero while evaluating a constant".to_string())
+        }
+        BinaryOperation::Power if right < 0 => {
+            return Err("Constant powers of ints need a non-negative exponent".to_string())
+        }
+        BinaryOperation::Add => left.checked_add(right),
+        BinaryOperation::Subtract => left.checked_sub(right),
+        BinaryOperation::Multiply => left.checked_mul(right),
+        // division of two ints truncates towards zero
+        BinaryOperation::Divide | BinaryOperation::IntegerDivide => left.checked_div(right),
+        BinaryOperation::Modulo => left.checked_rem(right),
+        BinaryOperation::Power => u32::try_from(right)
+            .ok()
+            .and_then(|exponent| left.checked_pow(exponent)),
+        BinaryOperation::BitwiseAnd => Some(left & right),
+        BinaryOperation::BitwiseOr => Some(left | right),
+        BinaryOperation::BitwiseXor => Some(left ^ right),
+        BinaryOperation::ShiftLeft => u32::try_from(right)
+            .ok()
+            .and_then(|shift| left.checked_shl(shift)),
+        BinaryOperation::ShiftRight => u32::try_from(right)
+            .ok()
+            .and_then(|shift| left.checked_shr(shift)),
+        _ => return Err("Assignments are not known at compile time".to_string()),
+    };
+
+    result.map(Value::Integer).ok_or_else(overflow)
+}
+
+fn evaluate_float_operation(
+    operation: &BinaryOperation,
+    left: f64,
+    right: f64,
+) -> Result<Value, String> {
+    let result = match operation {
+        BinaryOperation::Add => left + right,
+        BinaryOperation::Subtract => left - right,
+        BinaryOperation::Multiply => left * right,
+        BinaryOperation::Divide => left / right,
+        BinaryOperation::IntegerDivide => (left / right).trunc(),
+        BinaryOperation::Modulo => left % right,
+        BinaryOperation::Power => left.powf(right),
+        _ => return Err(format!("Cannot apply {:?} to nums", operation)),
+    };
+
+    if result.is_finite() {
+        Ok(Value::Float(result))
+    } else {
+        Err("Constant does not evaluate to a finite number".to_string())
+    }
+}
+
+/// Value of a constant of the declared type, integer literals declared as num are nums
+pub fn coerce_constant(value: Value, declared_type: &Type) -> Value {
+    match (value, declared_type) {
+        (Value::Integer(integer), Type::Number) => Value::Float(integer as f64),
+        (value, _) => value,
+    }
+}
+
+fn compare(left: Value, right: Value) -> Option<Ordering> {
+    match promote(left, right) {
+        (Value::Integer(left), Value::Integer(right)) => Some(left.cmp(&right)),
+        (Value::Float(left), Value::Float(right)) => left.partial_cmp(&right),
+        (Value::Boolean(left), Value::Boolean(right)) => Some(left.cmp(&right)),
+        (Value::Character(left), Value::Character(right)) => Some(left.cmp(&right)),
+        _ => None,
+    }
+}
+
+impl SymbolTable {
+    /**
+     * Value of an expression known at compile time, which only uses literals,
+     * constants and enum variants. Strings are not folded.
+     *
+     * Errs with the reason the value is not known otherwise
+     */
+    pub fn evaluate_constant(&self, expression: &Node<SyntaxNode>) -> Result<Value, String> {
+        let mut operands = expression.iter();
+
+        match &expression.data().component {
+            SyntaxComponent::Constant(constant) => match constant {
+                Constant::Integer(integer) => Ok(Value::Integer(*integer)),
+                Constant::Float(float) => Ok(Value::Float(*float)),
+                Constant::Boolean(boolean) => Ok(Value::Boolean(*boolean)),
+                Constant::Character(character) => Ok(Value::Character(*character)),
+                Constant::String(_) => Err("Strings are not folded at compile time".to_string()),
+            },
+            SyntaxComponent::Identifier(name) => self
+                .get(name)
+                .and_then(|symbol| symbol.constant)
+                .ok_or(format!("`{name}` is not a constant")),
+            SyntaxComponent::Member(variant) => {
+                let enum_name = self
+                    .referenced_enum(expression)
+                    .ok_or(format!("Field `{variant}` is not a constant"))?;
+                let position = self
+                    .types()
+                    .get_enum(&enum_name)
+                    .and_then(|definition| definition.position(variant))
+                    .ok_or(format!("Enum `{enum_name}` has no variant `{variant}`"))?;
+
+                Ok(Value::Integer(position as i64))
+            }
+            SyntaxComponent::UnaryOperation(operation) => {
+                if let UnaryOperation::Increment | UnaryOperation::Decrement = operation {
+                    return Err("Increments and decrements are not known at compile time".into());
+                }
+
+                match (operation, self.evaluate_constant(operands.next().unwrap())?) {
+                    (UnaryOperation::Minus, Value::Integer(integer)) => integer
+                        .checked_neg()
+                        .map(Value::Integer)
+                        .ok_or_else(overflow),
+                    (UnaryOperation::Minus, Value::Float(float)) => Ok(Value::Float(-float)),
+                    (UnaryOperation::Negation, Value::Boolean(boolean)) => {
+                        Ok(Value::Boolean(!boolean))
+                    }
+                    (UnaryOperation::BitwiseNot, Value::Integer(integer)) => {
+                        Ok(Value::Integer(!integer))
+                    }
+                    (operation, operand) => {
+                        Err(format!("Cannot apply {:?} to {:?}", operation, operand))
+                    }
+                }
+            }
+            SyntaxComponent::BinaryOperation(operation) => {
+                let left = self.evaluate_constant(operands.next().unwrap())?;
+                let right = self.evaluate_constant(operands.next().unwrap())?;
+
+                match promote(left, right) {
+                    (Value::Integer(left), Value::Integer(right)) => {
+                        evaluate_integer_operation(operation, left, right)
+                    }
+                    (Value::Float(left), Value::Float(right)) => {
+                        evaluate_float_operation(operation, left, right)
+                    }
+                    (left, right) => Err(format!(
+                        "Cannot apply {:?} to {:?} and {:?}",
+                        operation, left, right
+                    )),
+                }
+            }
+            // the right side is only evaluated when it decides the result, like at run time
+            SyntaxComponent::LogicalOperation(operation) => {
+                let left = self.evaluate_constant(operands.next().unwrap())?;
+                match (operation, left) {
+                    (LogicalOperation::And, Value::Boolean(false)) => Ok(Value::Boolean(false)),
+                    (LogicalOperation::Or, Value::Boolean(true)) => Ok(Value::Boolean(true)),
+                    _ => self.evaluate_constant(operands.next().unwrap()),
+                }
+            }
+            SyntaxComponent::Relation(relation) => {
+                let left = self.evaluate_constant(operands.next().unwrap())?;
+                let right = self.evaluate_constant(operands.next().unwrap())?;
+                let ordering = compare(left, right);
+
+                let holds = match relation {
+                    Relation::GreaterThan => ordering == Some(Ordering::Greater),
+                    Relation::GreaterThanOrEqual => {
+                        matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+                    }
+                    Relation::LessThan => ordering == Some(Ordering::Less),
+                    Relation::LessThanOrEqual => {
+                        matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+                    }
+                    Relation::EqualTo => ordering == Some(Ordering::Equal),
+                    Relation::NotEqualTo => ordering != Some(Ordering::Equal),
+                };
+
+                Ok(Value::Boolean(holds))
+            }
+            // both sides are evaluated, so that an int side picked next to a num one is a num
+            SyntaxComponent::Conditional => {
+                let condition = self.evaluate_constant(operands.next().unwrap())?;
+                let true_value = self.evaluate_constant(operands.next().unwrap())?;
+                let false_value = self.evaluate_constant(operands.next().unwrap())?;
+                let (true_value, false_value) = promote(true_value, false_value);
+
+                match condition {
+                    Value::Boolean(true) => Ok(true_value),
+                    _ => Ok(false_value),
+                }
+            }
+            SyntaxComponent::Conversion(target_type) => {
+                match (
+                    target_type,
+                    self.evaluate_constant(operands.next().unwrap())?,
+                ) {
+                    (Type::Integer, Value::Float(float)) => Ok(Value::Integer(float as i64)),
+                    (Type::Integer, Value::Character(character)) => {
+                        Ok(Value::Integer(character as i64))
+                    }
+                    (Type::Number, Value::Integer(integer)) => Ok(Value::Float(integer as f64)),
+                    (Type::Character, Value::Integer(integer)) => u32::try_from(integer)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .map(Value::Character)
+                        .ok_or(format!("{integer} is not a character code")),
+                    // converting to the same type does nothing
+                    (_, value) => Ok(value),
+                }
+            }
+            _ => Err("Only literals, constants and enum variants are known at compile time".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        expression::parse_expression,
+        lexical_analysis::lexical_analysis,
+        span::Span,
+        symbol_table::{DataType, Symbol},
+    };
+
+    // K is a constant and x a variable
+    fn evaluate(expression: &str) -> Result<Value, String> {
+        let mut symbol_table = SymbolTable::new();
+        for (name, constant) in [("K", Some(Value::Integer(5))), ("x", None)] {
+            symbol_table.insert(
+                name.into(),
+                Symbol {
+                    location: None,
+                    data_type: DataType::Integer,
+                    length: None,
+                    span: Span::default(),
+                    constant,
+                },
+            );
+        }
+
+        let (mut tokens, diagnostics) = lexical_analysis(expression.chars());
+        assert_eq!(diagnostics, vec![]);
+        let tree = parse_expression(&mut tokens).unwrap();
+
+        symbol_table.evaluate_constant(tree.root())
+    }
+
+    #[test]
+    fn it_folds_constant_expressions() {
+        assert!(matches!(evaluate("2 + 3 * 4"), Ok(Value::Integer(14))));
+        assert!(matches!(evaluate("K * 2 - 1"), Ok(Value::Integer(9))));
+        assert!(matches!(evaluate("-7 / 2"), Ok(Value::Integer(-3))));
+        assert!(matches!(evaluate("1 << 4 | 1"), Ok(Value::Integer(17))));
+        assert!(matches!(
+            evaluate("K > 3 && !false"),
+            Ok(Value::Boolean(true))
+        ));
+        assert!(matches!(evaluate("K < 3 ? 1 : 2"), Ok(Value::Integer(2))));
+        assert!(matches!(evaluate("int('a')"), Ok(Value::Integer(97))));
+    }
+
+    #[test]
+    fn it_promotes_integer_operands_next_to_nums() {
+        assert!(matches!(evaluate("K / 2.0"), Ok(Value::Float(float)) if float == 2.5));
+        assert!(matches!(evaluate("true ? 1 : 0.5"), Ok(Value::Float(float)) if float == 1.0));
+    }
+
+    #[test]
+    fn it_reports_overflow() {
+        assert_eq!(
+            evaluate("9223372036854775807 + 1").unwrap_err(),
+            "Overflow while evaluating a constant"
+        );
+        assert!(evaluate("2 ** 64").is_err());
+        assert!(evaluate("1 << 64").is_err());
+    }
+
+    #[test]
+    fn it_reports_division_by_zero() {
+        for expression in ["K / 0", "K % (K - 5)", "K \\ 0"] {
+            assert_eq!(
+                evaluate(expression).unwrap_err(),
+                "Division by zero while evaluating a constant",
+                "{expression}"
+            );
+        }
+        assert!(evaluate("1.0 / 0.0").is_err());
+    }
+
+    #[test]
+    fn it_rejects_non_constant_operands() {
+        assert_eq!(evaluate("x + 1").unwrap_err(), "`x` is not a constant");
+        assert_eq!(evaluate("y").unwrap_err(), "`y` is not a constant");
+        assert!(evaluate("K++").is_err());
+        assert!(evaluate("\"text\"").is_err());
+    }
+
+    #[test]
+    fn it_only_evaluates_the_right_side_of_logical_operations_when_needed() {
+        assert!(matches!(
+            evaluate("false && x > 1"),
+            Ok(Value::Boolean(false))
+        ));
+        assert!(evaluate("true && x > 1").is_err());
+    }
+}
@@ -1,25 +1,160 @@
-use std::collections::HashMap;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    fmt,
+    rc::Rc,
+};
 
-use crate::code_generation::Address;
+use trees::Node;
 
-#[derive(Clone, Debug)]
+mod constant_evaluation;
+mod type_registry;
+
+pub use constant_evaluation::*;
+pub use type_registry::*;
+
+use crate::{
+    code_generation::{Address, Value},
+    diagnostic::Diagnostic,
+    span::Span,
+    syntax_analysis::{SyntaxComponent, SyntaxNode, Type},
+};
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum DataType {
     Boolean,
+    Integer,
     Number,
+    String,
+    Character,
+    // structs are told apart by name, even when their fields are the same
+    Struct(String),
+    // stored as the position of the variant, but only comparable to the same enum
+    Enum(String),
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataType::Boolean => write!(f, "bool"),
+            DataType::Integer => write!(f, "int"),
+            DataType::Number => write!(f, "num"),
+            DataType::String => write!(f, "str"),
+            DataType::Character => write!(f, "char"),
+            DataType::Struct(name) => write!(f, "struct {name}"),
+            DataType::Enum(name) => write!(f, "enum {name}"),
+        }
+    }
+}
+
+// arrays convert to the type of their elements, their length is kept by the symbol
+impl From<&Type> for DataType {
+    fn from(type_component: &Type) -> Self {
+        match type_component {
+            Type::Boolean => DataType::Boolean,
+            Type::Integer => DataType::Integer,
+            Type::Number => DataType::Number,
+            Type::String => DataType::String,
+            Type::Character => DataType::Character,
+            Type::Struct(name) => DataType::Struct(name.clone()),
+            Type::Enum(name) => DataType::Enum(name.clone()),
+            Type::Array(element_type, _) => DataType::from(element_type.as_ref()),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct Symbol {
     pub location: Option<Address>,
     pub data_type: DataType,
+    // number of elements of arrays, None for scalars
+    pub length: Option<usize>,
+    // where the symbol was declared
+    pub span: Span,
+    // value of constants, known at compile time
+    pub constant: Option<Value>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FunctionSignature {
+    pub parameters: Vec<DataType>,
+    // None for functions without a result
+    pub return_type: Option<DataType>,
+    // where the function was defined
+    pub span: Span,
+}
+
+impl FunctionSignature {
+    /// Signature of a function definition node, as built by the syntax analysis
+    pub fn from_definition(definition: &Node<SyntaxNode>) -> Self {
+        let mut children = definition.iter();
+        let type_of = |node: &Node<SyntaxNode>| match &node.data().component {
+            SyntaxComponent::Type(type_component) => Some(DataType::from(type_component)),
+            _ => None,
+        };
+
+        let parameters = children
+            .next()
+            .map(|parameters| {
+                parameters
+                    .iter()
+                    .filter_map(|parameter| parameter.iter().next().and_then(type_of))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let return_type = children.next().and_then(type_of);
+
+        Self {
+            parameters,
+            return_type,
+            span: definition.data().span,
+        }
+    }
+}
+
+/// Labels of the innermost loop, where `break` and `continue` jump to, unless a switch is nested in it
+#[derive(Clone, Debug, Default)]
+pub struct LoopLabels {
+    pub break_label: String,
+    pub continue_label: String,
+}
+
+// counters are shared between the copies of the table made for nested scopes,
+// so temporaries and labels stay unique across the whole program
+#[derive(Debug, Default)]
+struct Counters {
+    temp: Cell<u32>,
+    for_loop: Cell<u32>,
+    while_loop: Cell<u32>,
+    do_while_loop: Cell<u32>,
+    if_statement: Cell<u32>,
+    logical: Cell<u32>,
+    switch_statement: Cell<u32>,
+    conditional: Cell<u32>,
+}
+
+fn next(counter: &Cell<u32>) -> u32 {
+    counter.set(counter.get() + 1);
+    counter.get()
 }
 
 #[derive(Clone, Debug)]
 pub struct SymbolTable {
     table: HashMap<String, Symbol>,
-    temp_count: u32,
-    for_count: u32,
-    if_count: u32,
+    // names of declarations that failed to parse, they are known but have no symbol
+    broken_declarations: HashSet<String>,
+    functions: HashMap<String, FunctionSignature>,
+    types: TypeRegistry,
+    enclosing_loop: Option<LoopLabels>,
+    // label after the innermost switch, None when a loop is nested deeper than any switch
+    enclosing_switch: Option<String>,
+    // return type of the function being analysed, None outside of functions
+    enclosing_function: Option<Option<DataType>>,
+    counters: Rc<Counters>,
+    // string constants of the whole program, shared like the counters
+    string_pool: Rc<RefCell<Vec<String>>>,
+    // warnings of the whole program, they do not stop the analysis
+    warnings: Rc<RefCell<Vec<Diagnostic>>>,
 }
 
 impl SymbolTable {
@@ -27,9 +162,15 @@ impl SymbolTable {
         let table = HashMap::new();
         Self {
             table,
-            temp_count: 0,
-            for_count: 0,
-            if_count: 0,
+            broken_declarations: HashSet::new(),
+            functions: HashMap::new(),
+            types: TypeRegistry::default(),
+            enclosing_loop: None,
+            enclosing_switch: None,
+            enclosing_function: None,
+            counters: Rc::new(Counters::default()),
+            string_pool: Rc::default(),
+            warnings: Rc::default(),
         }
     }
 
@@ -41,18 +182,172 @@ impl SymbolTable {
         self.table.insert(key, value)
     }
 
+    /// Remembers the name of a declaration the syntax analysis could not parse
+    pub fn insert_broken_declaration(&mut self, name: String) {
+        self.broken_declarations.insert(name);
+    }
+
+    /// Whether the name was only declared by a declaration that failed to parse
+    pub fn is_broken_declaration(&self, name: &String) -> bool {
+        !self.table.contains_key(name) && self.broken_declarations.contains(name)
+    }
+
+    pub fn get_function(&self, name: &String) -> Option<FunctionSignature> {
+        self.functions.get(name).cloned()
+    }
+
+    pub fn insert_function(
+        &mut self,
+        name: String,
+        signature: FunctionSignature,
+    ) -> Option<FunctionSignature> {
+        self.functions.insert(name, signature)
+    }
+
+    pub fn types(&self) -> &TypeRegistry {
+        &self.types
+    }
+
+    pub fn types_mut(&mut self) -> &mut TypeRegistry {
+        &mut self.types
+    }
+
+    /// Enum named by the base of a variant access such as Color.Red, variables shadow enums
+    pub fn referenced_enum(&self, member: &Node<SyntaxNode>) -> Option<String> {
+        if !matches!(member.data().component, SyntaxComponent::Member(_)) {
+            return None;
+        }
+
+        match &member.iter().next()?.data().component {
+            SyntaxComponent::Identifier(name)
+                if self.get(name).is_none() && self.types.get_enum(name).is_some() =>
+            {
+                Some(name.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /**
+     * Value of an int-like expression known at compile time, with characters
+     * as their code and enum variants as their position. None for other
+     * expressions.
+     */
+    pub fn constant_value(&self, constant: &Node<SyntaxNode>) -> Option<i64> {
+        match self.evaluate_constant(constant) {
+            Ok(Value::Integer(integer)) => Some(integer),
+            Ok(Value::Character(character)) => Some(character as i64),
+            _ => None,
+        }
+    }
+
+    /// Return type of the enclosing function, the outer None meaning there is no such function
+    pub fn enclosing_function(&self) -> Option<Option<DataType>> {
+        self.enclosing_function.clone()
+    }
+
+    /// Table for the body of a function, which only sees functions, types, constants and its own parameters
+    pub fn function_scope(&self, return_type: Option<DataType>) -> Self {
+        Self {
+            // constants are not stored anywhere, so functions can use them as well
+            table: self
+                .table
+                .iter()
+                .filter(|(_, symbol)| symbol.constant.is_some())
+                .map(|(name, symbol)| (name.clone(), symbol.clone()))
+                .collect(),
+            enclosing_loop: None,
+            enclosing_switch: None,
+            enclosing_function: Some(return_type),
+            ..self.clone()
+        }
+    }
+
+    pub fn enclosing_loop(&self) -> Option<&LoopLabels> {
+        self.enclosing_loop.as_ref()
+    }
+
+    /// Copy of the table for the body of a loop
+    pub fn loop_scope(&self, labels: LoopLabels) -> Self {
+        Self {
+            enclosing_loop: Some(labels),
+            enclosing_switch: None,
+            ..self.clone()
+        }
+    }
+
+    /// Copy of the table for the cases of a switch, which `break` leaves while `continue` still continues the enclosing loop
+    pub fn switch_scope(&self, after_label: String) -> Self {
+        Self {
+            enclosing_switch: Some(after_label),
+            ..self.clone()
+        }
+    }
+
+    /// Where `break` jumps to, after the innermost loop or switch
+    pub fn break_label(&self) -> Option<String> {
+        self.enclosing_switch.clone().or_else(|| {
+            self.enclosing_loop
+                .as_ref()
+                .map(|labels| labels.break_label.clone())
+        })
+    }
+
     pub fn new_temp(&mut self) -> u32 {
-        self.temp_count += 1;
-        self.temp_count
+        next(&self.counters.temp)
     }
 
     pub fn new_if(&mut self) -> u32 {
-        self.if_count += 1;
-        self.if_count
+        next(&self.counters.if_statement)
     }
 
     pub fn new_for(&mut self) -> u32 {
-        self.for_count += 1;
-        self.for_count
+        next(&self.counters.for_loop)
+    }
+
+    pub fn new_while(&mut self) -> u32 {
+        next(&self.counters.while_loop)
+    }
+
+    pub fn new_do_while(&mut self) -> u32 {
+        next(&self.counters.do_while_loop)
+    }
+
+    pub fn new_logical(&mut self) -> u32 {
+        next(&self.counters.logical)
+    }
+
+    pub fn new_switch(&mut self) -> u32 {
+        next(&self.counters.switch_statement)
+    }
+
+    pub fn new_conditional(&mut self) -> u32 {
+        next(&self.counters.conditional)
+    }
+
+    /// Index of `value` in the string pool, equal strings share an entry
+    pub fn intern_string(&mut self, value: &str) -> u32 {
+        let mut string_pool = self.string_pool.borrow_mut();
+        let index = match string_pool.iter().position(|entry| entry == value) {
+            Some(index) => index,
+            None => {
+                string_pool.push(value.to_string());
+                string_pool.len() - 1
+            }
+        };
+
+        index as u32
+    }
+
+    pub fn string_pool(&self) -> Vec<String> {
+        self.string_pool.borrow().clone()
+    }
+
+    pub fn warn(&self, warning: Diagnostic) {
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    pub fn warnings(&self) -> Vec<Diagnostic> {
+        self.warnings.borrow().clone()
     }
 }
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use trees::Node;
+
+use crate::{
+    span::Span,
+    syntax_analysis::{SyntaxComponent, SyntaxNode},
+};
+
+use super::DataType;
+
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub name: String,
+    pub data_type: DataType,
+    // first slot of the field within the struct
+    pub offset: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct StructDefinition {
+    pub fields: Vec<Field>,
+    // number of slots taken by a value of the struct, nested structs are stored inline
+    pub size: usize,
+    // where the struct was defined
+    pub span: Span,
+}
+
+impl StructDefinition {
+    /**
+     * Definition of a struct definition node, as built by the syntax analysis.
+     *
+     * Every field takes a slot, except fields holding another struct, which
+     * take as many slots as that struct. The structs used by the fields must
+     * already be registered.
+     */
+    pub fn from_definition(definition: &Node<SyntaxNode>, types: &TypeRegistry) -> Self {
+        let mut fields = vec![];
+        let mut size = 0;
+
+        for field in definition.iter() {
+            let mut children = field.iter();
+            let data_type = match children.next().map(|node| &node.data().component) {
+                Some(SyntaxComponent::Type(type_component)) => DataType::from(type_component),
+                _ => continue,
+            };
+            let Some(SyntaxComponent::Identifier(name)) =
+                children.next().map(|node| &node.data().component)
+            else {
+                continue;
+            };
+
+            let field_size = types.size_of(&data_type);
+            fields.push(Field {
+                name: name.clone(),
+                data_type,
+                offset: size,
+            });
+            size += field_size;
+        }
+
+        Self {
+            fields,
+            size,
+            span: definition.data().span,
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EnumDefinition {
+    // variants in definition order, a variant is stored as its position
+    pub variants: Vec<String>,
+    // where the enum was defined
+    pub span: Span,
+}
+
+impl EnumDefinition {
+    pub fn from_definition(definition: &Node<SyntaxNode>) -> Self {
+        let variants = definition
+            .iter()
+            .filter_map(|variant| match &variant.data().component {
+                SyntaxComponent::Identifier(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            variants,
+            span: definition.data().span,
+        }
+    }
+
+    pub fn position(&self, variant: &str) -> Option<usize> {
+        self.variants.iter().position(|name| name == variant)
+    }
+}
+
+/// Types defined by the program, looked up by name
+#[derive(Clone, Debug, Default)]
+pub struct TypeRegistry {
+    structs: HashMap<String, StructDefinition>,
+    enums: HashMap<String, EnumDefinition>,
+}
+
+impl TypeRegistry {
+    pub fn get_struct(&self, name: &str) -> Option<&StructDefinition> {
+        self.structs.get(name)
+    }
+
+    pub fn insert_struct(
+        &mut self,
+        name: String,
+        definition: StructDefinition,
+    ) -> Option<StructDefinition> {
+        self.structs.insert(name, definition)
+    }
+
+    pub fn get_enum(&self, name: &str) -> Option<&EnumDefinition> {
+        self.enums.get(name)
+    }
+
+    pub fn insert_enum(
+        &mut self,
+        name: String,
+        definition: EnumDefinition,
+    ) -> Option<EnumDefinition> {
+        self.enums.insert(name, definition)
+    }
+
+    /// Where the struct or enum with the name was defined
+    pub fn definition_span(&self, name: &str) -> Option<Span> {
+        self.get_struct(name)
+            .map(|definition| definition.span)
+            .or_else(|| self.get_enum(name).map(|definition| definition.span))
+    }
+
+    /// Number of slots taken by a value of the type
+    pub fn size_of(&self, data_type: &DataType) -> usize {
+        match data_type {
+            DataType::Struct(name) => self
+                .get_struct(name)
+                .map_or(1, |definition| definition.size),
+            _ => 1,
+        }
+    }
+}
@@ -1,6 +1,12 @@
+use std::fmt;
+
 use trees::Tree;
 
-use crate::token::Token;
+use crate::{
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    span::Span,
+    token::Token,
+};
 
 #[derive(Debug, PartialEq)]
 pub enum Relation {
@@ -22,25 +28,136 @@ pub enum BinaryOperation {
     MultiplyBy,
     Divide,
     DivideBy,
+    IntegerDivide,
+    IntegerDivideBy,
+    Modulo,
+    ModuloBy,
+    Power,
+    PowerBy,
+    BitwiseAnd,
+    BitwiseAndBy,
+    BitwiseOr,
+    BitwiseOrBy,
+    BitwiseXor,
+    BitwiseXorBy,
+    ShiftLeft,
+    ShiftLeftBy,
+    ShiftRight,
+    ShiftRightBy,
+}
+
+impl BinaryOperation {
+    /// Whether the operation stores its result in its left operand, e.g. +=
+    pub fn is_compound_assignment(&self) -> bool {
+        matches!(
+            self,
+            Self::IncreaseBy
+                | Self::DecreaseBy
+                | Self::MultiplyBy
+                | Self::DivideBy
+                | Self::IntegerDivideBy
+                | Self::ModuloBy
+                | Self::PowerBy
+                | Self::BitwiseAndBy
+                | Self::BitwiseOrBy
+                | Self::BitwiseXorBy
+                | Self::ShiftLeftBy
+                | Self::ShiftRightBy
+        )
+    }
+
+    /// Whether the operation works on the bits of integral operands
+    pub fn is_bitwise(&self) -> bool {
+        matches!(
+            self,
+            Self::BitwiseAnd
+                | Self::BitwiseAndBy
+                | Self::BitwiseOr
+                | Self::BitwiseOrBy
+                | Self::BitwiseXor
+                | Self::BitwiseXorBy
+                | Self::ShiftLeft
+                | Self::ShiftLeftBy
+                | Self::ShiftRight
+                | Self::ShiftRightBy
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LogicalOperation {
+    And,
+    Or,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum UnaryOperation {
     Increment,
     Decrement,
+    // boolean not
     Negation,
+    // numeric negation, e.g. -x
+    Minus,
+    // flips every bit of an integral value, e.g. ~x
+    BitwiseNot,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Constant {
-    Float(f32),
+    Float(f64),
+    Integer(i64),
     Boolean(bool),
+    String(String),
+    Character(char),
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Type {
     Number,
+    Integer,
     Boolean,
+    String,
+    Character,
+    // struct defined by the program, e.g. struct Point
+    Struct(String),
+    // enum defined by the program, e.g. enum Color
+    Enum(String),
+    // fixed number of elements of the same type, e.g. num[10]
+    Array(Box<Type>, usize),
+}
+
+impl Type {
+    /// Type of the elements of an array, the type itself for scalars
+    pub fn element_type(&self) -> &Type {
+        match self {
+            Type::Array(element_type, _) => element_type,
+            scalar_type => scalar_type,
+        }
+    }
+
+    /// Number of elements of an array, None for scalars
+    pub fn array_length(&self) -> Option<usize> {
+        match self {
+            Type::Array(_, length) => Some(*length),
+            _ => None,
+        }
+    }
+}
+
+// types are shown the way they are written in the source
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Number => write!(f, "num"),
+            Type::Integer => write!(f, "int"),
+            Type::Boolean => write!(f, "bool"),
+            Type::String => write!(f, "str"),
+            Type::Character => write!(f, "char"),
+            Type::Struct(name) => write!(f, "struct {name}"),
+            Type::Enum(name) => write!(f, "enum {name}"),
+            Type::Array(element_type, length) => write!(f, "{element_type}[{length}]"),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -50,30 +167,125 @@ pub enum SyntaxComponent {
     Sequence,
     If,
     For,
+    While,
+    DoWhile,
+    Break,
+    Continue,
+    // writes its children to standard output
+    Print,
+    // number read from standard input
+    Read,
+    // function definition, holding the parameters, the return type (Null without one) and the body
+    Function(String),
+    // declarations of the parameters of a function
+    Parameters,
+    Return,
+    // function call, holding the arguments
+    Call(String),
+    // struct definition, holding the declarations of the fields
+    Struct(String),
+    // enum definition, holding the identifiers of the variants
+    Enum(String),
+    // holding the value switched on, the cases and the default case, if any
+    Switch,
+    // holding the labels followed by the body
+    Case,
+    // holding the body
+    Default,
     Assignment,
     Declaration,
+    // declaration of a value known at compile time, which cannot be changed
+    ConstantDeclaration,
     Type(Type),
     Relation(Relation),
     BinaryOperation(BinaryOperation),
+    LogicalOperation(LogicalOperation),
     UnaryOperation(UnaryOperation),
+    // holding the condition and the values picked when it holds and when it does not
+    Conditional,
+    // explicit conversion between numbers and characters, e.g. int(x) or char(65)
+    Conversion(Type),
+    // elements of an array, e.g. [1, 2, 3]
+    ArrayLiteral,
+    // element of an array, holding the array and the index
+    Index,
+    // field of a struct, holding the struct
+    Member(String),
     Constant(Constant),
     Identifier(String),
+    // statement that could not be parsed, replaced while recovering from the error
+    Error(Diagnostic),
 }
 
-pub type AbstractSyntaxTree = Tree<SyntaxComponent>;
+/// A syntax component along with the source it was parsed from
+#[derive(Debug, PartialEq)]
+pub struct SyntaxNode {
+    pub component: SyntaxComponent,
+    pub span: Span,
+}
+
+impl SyntaxNode {
+    pub fn new(component: SyntaxComponent, span: Span) -> Self {
+        Self { component, span }
+    }
+}
+
+impl From<SyntaxComponent> for SyntaxNode {
+    fn from(component: SyntaxComponent) -> Self {
+        Self::new(component, Span::default())
+    }
+}
+
+pub type AbstractSyntaxTree = Tree<SyntaxNode>;
+
+pub fn new_node(component: SyntaxComponent, span: Span) -> AbstractSyntaxTree {
+    AbstractSyntaxTree::new(SyntaxNode::new(component, span))
+}
+
+/// Grows the span of the root of `tree` so that it also covers `span`
+pub fn extend_span(tree: &mut AbstractSyntaxTree, span: Span) {
+    let mut root = tree.root_mut();
+    let node = root.data_mut();
+    node.span = node.span.to(span);
+}
 
 impl TryFrom<Token> for SyntaxComponent {
-    type Error = String;
+    type Error = Diagnostic;
 
-    fn try_from(token: Token) -> Result<Self, Self::Error> {
+    fn try_from(token: Token) -> Result<Self, Diagnostic> {
         let ok_value = match token {
             Token::If => Self::If,
             Token::For => Self::For,
+            Token::While => Self::While,
+            Token::Do => Self::DoWhile,
+            Token::Break => Self::Break,
+            Token::Continue => Self::Continue,
+            Token::Print => Self::Print,
+            Token::Read => Self::Read,
+            Token::Return => Self::Return,
 
             Token::OperatorMultiplication => Self::BinaryOperation(BinaryOperation::Multiply),
             Token::OperatorMultiplyBy => Self::BinaryOperation(BinaryOperation::MultiplyBy),
             Token::OperatorDivision => Self::BinaryOperation(BinaryOperation::Divide),
             Token::OperatorDivideBy => Self::BinaryOperation(BinaryOperation::DivideBy),
+            Token::OperatorIntegerDivision => Self::BinaryOperation(BinaryOperation::IntegerDivide),
+            Token::OperatorIntegerDivideBy => {
+                Self::BinaryOperation(BinaryOperation::IntegerDivideBy)
+            }
+            Token::OperatorModulo => Self::BinaryOperation(BinaryOperation::Modulo),
+            Token::OperatorModuloBy => Self::BinaryOperation(BinaryOperation::ModuloBy),
+            Token::OperatorPower => Self::BinaryOperation(BinaryOperation::Power),
+            Token::OperatorPowerBy => Self::BinaryOperation(BinaryOperation::PowerBy),
+            Token::OperatorBitwiseAnd => Self::BinaryOperation(BinaryOperation::BitwiseAnd),
+            Token::OperatorBitwiseAndBy => Self::BinaryOperation(BinaryOperation::BitwiseAndBy),
+            Token::OperatorBitwiseOr => Self::BinaryOperation(BinaryOperation::BitwiseOr),
+            Token::OperatorBitwiseOrBy => Self::BinaryOperation(BinaryOperation::BitwiseOrBy),
+            Token::OperatorBitwiseXor => Self::BinaryOperation(BinaryOperation::BitwiseXor),
+            Token::OperatorBitwiseXorBy => Self::BinaryOperation(BinaryOperation::BitwiseXorBy),
+            Token::OperatorShiftLeft => Self::BinaryOperation(BinaryOperation::ShiftLeft),
+            Token::OperatorShiftLeftBy => Self::BinaryOperation(BinaryOperation::ShiftLeftBy),
+            Token::OperatorShiftRight => Self::BinaryOperation(BinaryOperation::ShiftRight),
+            Token::OperatorShiftRightBy => Self::BinaryOperation(BinaryOperation::ShiftRightBy),
             Token::OperatorAddition => Self::BinaryOperation(BinaryOperation::Add),
             Token::OperatorSubtraction => Self::BinaryOperation(BinaryOperation::Subtract),
             Token::OperatorIncreaseBy => Self::BinaryOperation(BinaryOperation::IncreaseBy),
@@ -82,6 +294,7 @@ impl TryFrom<Token> for SyntaxComponent {
             Token::OperatorIncrement => Self::UnaryOperation(UnaryOperation::Increment),
             Token::OperatorDecrement => Self::UnaryOperation(UnaryOperation::Decrement),
             Token::Not => Self::UnaryOperation(UnaryOperation::Negation),
+            Token::OperatorBitwiseNot => Self::UnaryOperation(UnaryOperation::BitwiseNot),
 
             Token::OperatorAssignment => Self::Assignment,
 
@@ -91,14 +304,20 @@ impl TryFrom<Token> for SyntaxComponent {
             Token::OperatorGreaterThanOrEqual => Self::Relation(Relation::GreaterThanOrEqual),
             Token::OperatorEqual => Self::Relation(Relation::EqualTo),
             Token::OperatorNotEqual => Self::Relation(Relation::NotEqualTo),
+            Token::OperatorAnd => Self::LogicalOperation(LogicalOperation::And),
+            Token::OperatorOr => Self::LogicalOperation(LogicalOperation::Or),
             Token::Identifier(name) => Self::Identifier(name),
             Token::Constant(value) => Self::Constant(Constant::Float(value)),
+            Token::IntegerConstant(value) => Self::Constant(Constant::Integer(value)),
+            Token::StringLiteral(value) => Self::Constant(Constant::String(value)),
+            Token::CharacterLiteral(value) => Self::Constant(Constant::Character(value)),
             Token::True => Self::Constant(Constant::Boolean(true)),
             Token::False => Self::Constant(Constant::Boolean(false)),
             _ => {
-                return Err(format!(
-                    "Token {} does not represent a syntax component",
-                    token
+                return Err(Diagnostic::error(
+                    Phase::Syntax,
+                    ErrorCode::UnexpectedToken,
+                    format!("Token {} does not represent a syntax component", token),
                 ))
             }
         };
@@ -108,26 +327,42 @@ impl TryFrom<Token> for SyntaxComponent {
 
 impl SyntaxComponent {
     pub fn is_identifier(&self) -> bool {
-        if let Self::Identifier(_) = self {
-            true
-        } else {
-            false
-        }
+        matches!(self, Self::Identifier(_))
+    }
+
+    /// Whether values can be stored in the component, i.e. variables, array elements and fields
+    pub fn is_assignment_target(&self) -> bool {
+        matches!(self, Self::Identifier(_) | Self::Index | Self::Member(_))
+    }
+
+    /// Whether the component is a part of an array or a struct
+    pub fn is_element_access(&self) -> bool {
+        matches!(self, Self::Index | Self::Member(_))
     }
 
-    pub fn try_get_identifier_name(&self) -> Result<String, String> {
+    /// Name of an identifier, errs as malformed in the given phase for any other component
+    pub fn try_get_identifier_name(&self, phase: Phase) -> Result<String, Diagnostic> {
         if let Self::Identifier(name) = self {
             Ok(name.clone())
         } else {
-            Err("Cannot get identifier name from non-identifier. Check your assumptions".into())
+            Err(Diagnostic::error(
+                phase,
+                ErrorCode::MalformedSyntaxTree,
+                "Cannot get identifier name from non-identifier. Check your assumptions",
+            ))
         }
     }
 
-    pub fn try_get_type(&self) -> Result<Type, String> {
+    /// Type of a type component, errs as malformed in the given phase for any other component
+    pub fn try_get_type(&self, phase: Phase) -> Result<Type, Diagnostic> {
         if let Self::Type(data_type) = self {
             Ok(data_type.clone())
         } else {
-            Err("Cannot get identifier name from non-identifier. Check your assumptions".into())
+            Err(Diagnostic::error(
+                phase,
+                ErrorCode::MalformedSyntaxTree,
+                "Cannot get type from non-type. Check your assumptions",
+            ))
         }
     }
 }
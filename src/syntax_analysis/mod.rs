@@ -1,4 +1,5 @@
 mod abstract_syntax_tree;
+#[allow(clippy::module_inception)]
 mod syntax_analysis;
 
 pub use abstract_syntax_tree::*;
@@ -1,46 +1,85 @@
 use std::iter::Peekable;
 
+use trees::Node;
+
 use crate::{
-    statement::parse_statement,
-    token::{expect_token, Token},
+    diagnostic::{Diagnostic, Phase},
+    statement::parse_top_level_statement,
+    token::{expect_token, SpannedToken, Token},
 };
 
-use super::{AbstractSyntaxTree, SyntaxComponent};
+use super::{extend_span, new_node, AbstractSyntaxTree, SyntaxComponent, SyntaxNode};
+
+fn collect_errors(node: &Node<SyntaxNode>, diagnostics: &mut Vec<Diagnostic>) {
+    if let SyntaxComponent::Error(diagnostic) = &node.data().component {
+        // errors caused by error tokens were already reported by the lexical analysis
+        if diagnostic.phase == Phase::Syntax {
+            diagnostics.push(diagnostic.clone());
+        }
+    }
+
+    for child in node.iter() {
+        collect_errors(child, diagnostics);
+    }
+}
 
+/**
+ * Parses the whole token stream.
+ *
+ * Syntax errors do not stop the analysis; broken statements show up in the
+ * tree as error nodes and every error is returned along with it.
+ */
 pub fn syntax_analysis(
-    tokens: &mut Peekable<impl Iterator<Item = Token>>,
-) -> Result<AbstractSyntaxTree, String> {
-    let mut root = AbstractSyntaxTree::new(SyntaxComponent::Sequence);
+    tokens: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+) -> (AbstractSyntaxTree, Vec<Diagnostic>) {
+    let start = tokens.peek().map(|token| token.span).unwrap_or_default();
+    let mut root = new_node(SyntaxComponent::Sequence, start);
+    let mut diagnostics = vec![];
 
     while let Some(token) = tokens.peek() {
-        if *token == Token::EOF {
+        if token.token == Token::EOF {
             break;
         }
-        root.push_back(parse_statement(tokens)?);
+        match parse_top_level_statement(tokens) {
+            Ok(statement) => root.push_back(statement),
+            Err(diagnostic) => diagnostics.push(diagnostic),
+        }
+    }
+
+    match expect_token(tokens, Token::EOF) {
+        Ok(eof) => extend_span(&mut root, eof.span),
+        Err(diagnostic) => diagnostics.push(diagnostic),
     }
 
-    expect_token(tokens, Token::EOF)?;
+    let mut errors = vec![];
+    collect_errors(root.root(), &mut errors);
+    errors.append(&mut diagnostics);
 
-    Ok(root)
+    (root, errors)
 }
 
 #[cfg(test)]
 mod tests {
-    use trees::tr;
-
     use super::*;
     use crate::{
-        syntax_analysis::{BinaryOperation, Constant, Relation, Type, UnaryOperation},
+        span::Span,
+        syntax_analysis::{
+            BinaryOperation, Constant, LogicalOperation, Relation, Type, UnaryOperation,
+        },
         token::Token,
     };
 
+    fn tr(component: SyntaxComponent) -> AbstractSyntaxTree {
+        trees::tr(component.into())
+    }
+
     fn assert_tokens_parse_to(tokens: Vec<Token>, expected: AbstractSyntaxTree) {
-        let result = syntax_analysis(&mut tokens.into_iter().peekable());
-        if let Err(message) = &result {
-            println!("{}", message);
-        }
-        assert!(result.is_ok());
-        let actual = result.unwrap();
+        let mut spanned_tokens = tokens
+            .into_iter()
+            .map(|token| SpannedToken::new(token, Span::default()))
+            .peekable();
+        let (actual, diagnostics) = syntax_analysis(&mut spanned_tokens);
+        assert_eq!(diagnostics, vec![]);
         assert_eq!(actual, expected);
     }
 
@@ -69,6 +108,41 @@ mod tests {
         assert_tokens_parse_to(tokens, expected);
     }
 
+    #[test]
+    fn it_parses_else_if_chains() {
+        // if (foo) {} else if (bar) {} else {}
+        let tokens = vec![
+            Token::If,
+            Token::ParenthesisOpening,
+            Token::Identifier("foo".into()),
+            Token::ParenthesisClosing,
+            Token::CurlyOpening,
+            Token::CurlyClosing,
+            Token::Else,
+            Token::If,
+            Token::ParenthesisOpening,
+            Token::Identifier("bar".into()),
+            Token::ParenthesisClosing,
+            Token::CurlyOpening,
+            Token::CurlyClosing,
+            Token::Else,
+            Token::CurlyOpening,
+            Token::CurlyClosing,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::If)
+                / tr(SyntaxComponent::Identifier("foo".into()))
+                / tr(SyntaxComponent::Sequence)
+                / (tr(SyntaxComponent::If)
+                    / tr(SyntaxComponent::Identifier("bar".into()))
+                    / tr(SyntaxComponent::Sequence)
+                    / tr(SyntaxComponent::Sequence)));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
     #[test]
     fn it_parses_for_statements() {
         // for (i = 0; i < 100; i++) {}
@@ -105,6 +179,97 @@ mod tests {
 
         assert_tokens_parse_to(tokens, expected);
     }
+    #[test]
+    fn it_parses_while_statements() {
+        // while (foo) i++; do i--; while (bar);
+        let tokens = vec![
+            Token::While,
+            Token::ParenthesisOpening,
+            Token::Identifier("foo".into()),
+            Token::ParenthesisClosing,
+            Token::Identifier("i".into()),
+            Token::OperatorIncrement,
+            Token::Semicolon,
+            Token::Do,
+            Token::Identifier("i".into()),
+            Token::OperatorDecrement,
+            Token::Semicolon,
+            Token::While,
+            Token::ParenthesisOpening,
+            Token::Identifier("bar".into()),
+            Token::ParenthesisClosing,
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::While)
+                / tr(SyntaxComponent::Identifier("foo".into()))
+                / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::Increment))
+                    / tr(SyntaxComponent::Identifier("i".into()))))
+            / (tr(SyntaxComponent::DoWhile)
+                / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::Decrement))
+                    / tr(SyntaxComponent::Identifier("i".into())))
+                / tr(SyntaxComponent::Identifier("bar".into())));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_jump_statements() {
+        // while (foo) { break; continue; }
+        let tokens = vec![
+            Token::While,
+            Token::ParenthesisOpening,
+            Token::Identifier("foo".into()),
+            Token::ParenthesisClosing,
+            Token::CurlyOpening,
+            Token::Break,
+            Token::Semicolon,
+            Token::Continue,
+            Token::Semicolon,
+            Token::CurlyClosing,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::While)
+                / tr(SyntaxComponent::Identifier("foo".into()))
+                / (tr(SyntaxComponent::Sequence)
+                    / tr(SyntaxComponent::Break)
+                    / tr(SyntaxComponent::Continue)));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_print_statements() {
+        // print("sum", read() + 1);
+        let tokens = vec![
+            Token::Print,
+            Token::ParenthesisOpening,
+            Token::StringLiteral("sum".into()),
+            Token::Comma,
+            Token::Read,
+            Token::ParenthesisOpening,
+            Token::ParenthesisClosing,
+            Token::OperatorAddition,
+            Token::IntegerConstant(1),
+            Token::ParenthesisClosing,
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Print)
+                / tr(SyntaxComponent::Constant(Constant::String("sum".into())))
+                / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Add))
+                    / tr(SyntaxComponent::Read)
+                    / tr(SyntaxComponent::Constant(Constant::Integer(1)))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
     #[test]
     fn it_parses_block_statements() {
         // { i = 0; j = 1; j++; }
@@ -166,20 +331,207 @@ mod tests {
         let expected = tr(SyntaxComponent::Sequence)
             / (tr(SyntaxComponent::Assignment)
                 / tr(SyntaxComponent::Identifier("i".into()))
-                / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Add))
-                    / tr(SyntaxComponent::Constant(Constant::Float(1.0)))
+                / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Subtract))
                     / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Add))
-                        / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Multiply))
-                            / tr(SyntaxComponent::Constant(Constant::Float(2.0)))
+                        / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Add))
+                            / tr(SyntaxComponent::Constant(Constant::Float(1.0)))
                             / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Divide))
-                                / tr(SyntaxComponent::Constant(Constant::Float(3.0)))
+                                / (tr(SyntaxComponent::BinaryOperation(
+                                    BinaryOperation::Multiply,
+                                )) / tr(SyntaxComponent::Constant(Constant::Float(2.0)))
+                                    / tr(SyntaxComponent::Constant(Constant::Float(3.0))))
                                 / tr(SyntaxComponent::Constant(Constant::Float(4.0)))))
-                        / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Subtract))
-                            / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Multiply))
-                                / tr(SyntaxComponent::Constant(Constant::Float(5.0)))
-                                / tr(SyntaxComponent::Constant(Constant::Float(6.0))))
-                            / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::Decrement))
-                                / tr(SyntaxComponent::Identifier("j".into())))))));
+                        / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Multiply))
+                            / tr(SyntaxComponent::Constant(Constant::Float(5.0)))
+                            / tr(SyntaxComponent::Constant(Constant::Float(6.0)))))
+                    / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::Decrement))
+                        / tr(SyntaxComponent::Identifier("j".into())))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_associates_operators() {
+        // a - b - c; a = b *= c;
+        let tokens = vec![
+            Token::Identifier("a".into()),
+            Token::OperatorSubtraction,
+            Token::Identifier("b".into()),
+            Token::OperatorSubtraction,
+            Token::Identifier("c".into()),
+            Token::Semicolon,
+            Token::Identifier("a".into()),
+            Token::OperatorAssignment,
+            Token::Identifier("b".into()),
+            Token::OperatorMultiplyBy,
+            Token::Identifier("c".into()),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Subtract))
+                / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Subtract))
+                    / tr(SyntaxComponent::Identifier("a".into()))
+                    / tr(SyntaxComponent::Identifier("b".into())))
+                / tr(SyntaxComponent::Identifier("c".into())))
+            / (tr(SyntaxComponent::Assignment)
+                / tr(SyntaxComponent::Identifier("a".into()))
+                / (tr(SyntaxComponent::BinaryOperation(
+                    BinaryOperation::MultiplyBy,
+                )) / tr(SyntaxComponent::Identifier("b".into()))
+                    / tr(SyntaxComponent::Identifier("c".into()))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_groups_parenthesized_expressions() {
+        // foo > (5 + 1) * !bar++;
+        let tokens = vec![
+            Token::Identifier("foo".into()),
+            Token::OperatorGreaterThan,
+            Token::ParenthesisOpening,
+            Token::Constant(5.0),
+            Token::OperatorAddition,
+            Token::Constant(1.0),
+            Token::ParenthesisClosing,
+            Token::OperatorMultiplication,
+            Token::Not,
+            Token::Identifier("bar".into()),
+            Token::OperatorIncrement,
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Relation(Relation::GreaterThan))
+                / tr(SyntaxComponent::Identifier("foo".into()))
+                / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Multiply))
+                    / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Add))
+                        / tr(SyntaxComponent::Constant(Constant::Float(5.0)))
+                        / tr(SyntaxComponent::Constant(Constant::Float(1.0))))
+                    / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::Negation))
+                        / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::Increment))
+                            / tr(SyntaxComponent::Identifier("bar".into()))))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_binds_logical_operators_below_relations() {
+        // a || b && c == d;
+        let tokens = vec![
+            Token::Identifier("a".into()),
+            Token::OperatorOr,
+            Token::Identifier("b".into()),
+            Token::OperatorAnd,
+            Token::Identifier("c".into()),
+            Token::OperatorEqual,
+            Token::Identifier("d".into()),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::LogicalOperation(LogicalOperation::Or))
+                / tr(SyntaxComponent::Identifier("a".into()))
+                / (tr(SyntaxComponent::LogicalOperation(LogicalOperation::And))
+                    / tr(SyntaxComponent::Identifier("b".into()))
+                    / (tr(SyntaxComponent::Relation(Relation::EqualTo))
+                        / tr(SyntaxComponent::Identifier("c".into()))
+                        / tr(SyntaxComponent::Identifier("d".into())))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_unary_minus() {
+        // x = -5 - -x;
+        let tokens = vec![
+            Token::Identifier("x".into()),
+            Token::OperatorAssignment,
+            Token::OperatorSubtraction,
+            Token::Constant(5.0),
+            Token::OperatorSubtraction,
+            Token::OperatorSubtraction,
+            Token::Identifier("x".into()),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Assignment)
+                / tr(SyntaxComponent::Identifier("x".into()))
+                / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Subtract))
+                    / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::Minus))
+                        / tr(SyntaxComponent::Constant(Constant::Float(5.0))))
+                    / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::Minus))
+                        / tr(SyntaxComponent::Identifier("x".into())))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_power_right_associatively() {
+        // -a ** b ** c % d;
+        let tokens = vec![
+            Token::OperatorSubtraction,
+            Token::Identifier("a".into()),
+            Token::OperatorPower,
+            Token::Identifier("b".into()),
+            Token::OperatorPower,
+            Token::Identifier("c".into()),
+            Token::OperatorModulo,
+            Token::Identifier("d".into()),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Modulo))
+                / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::Minus))
+                    / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Power))
+                        / tr(SyntaxComponent::Identifier("a".into()))
+                        / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Power))
+                            / tr(SyntaxComponent::Identifier("b".into()))
+                            / tr(SyntaxComponent::Identifier("c".into())))))
+                / tr(SyntaxComponent::Identifier("d".into())));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_bitwise_operators_by_precedence() {
+        // a | b ^ c & ~d << 1;
+        let tokens = vec![
+            Token::Identifier("a".into()),
+            Token::OperatorBitwiseOr,
+            Token::Identifier("b".into()),
+            Token::OperatorBitwiseXor,
+            Token::Identifier("c".into()),
+            Token::OperatorBitwiseAnd,
+            Token::OperatorBitwiseNot,
+            Token::Identifier("d".into()),
+            Token::OperatorShiftLeft,
+            Token::Constant(1.0),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::BitwiseOr))
+                / tr(SyntaxComponent::Identifier("a".into()))
+                / (tr(SyntaxComponent::BinaryOperation(
+                    BinaryOperation::BitwiseXor,
+                )) / tr(SyntaxComponent::Identifier("b".into()))
+                    / (tr(SyntaxComponent::BinaryOperation(
+                        BinaryOperation::BitwiseAnd,
+                    )) / tr(SyntaxComponent::Identifier("c".into()))
+                        / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::ShiftLeft))
+                            / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::BitwiseNot))
+                                / tr(SyntaxComponent::Identifier("d".into())))
+                            / tr(SyntaxComponent::Constant(Constant::Float(1.0)))))));
 
         assert_tokens_parse_to(tokens, expected);
     }
@@ -214,6 +566,405 @@ mod tests {
         assert_tokens_parse_to(tokens, expected);
     }
 
+    #[test]
+    fn it_parses_numeric_conversions() {
+        // int count = int(total / 2) + 1;
+        let tokens = vec![
+            Token::IntType,
+            Token::Identifier("count".into()),
+            Token::OperatorAssignment,
+            Token::IntType,
+            Token::ParenthesisOpening,
+            Token::Identifier("total".into()),
+            Token::OperatorDivision,
+            Token::IntegerConstant(2),
+            Token::ParenthesisClosing,
+            Token::OperatorAddition,
+            Token::IntegerConstant(1),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Declaration)
+                / (tr(SyntaxComponent::Type(Type::Integer)))
+                / (tr(SyntaxComponent::Identifier("count".into())))
+                / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Add))
+                    / (tr(SyntaxComponent::Conversion(Type::Integer))
+                        / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Divide))
+                            / tr(SyntaxComponent::Identifier("total".into()))
+                            / tr(SyntaxComponent::Constant(Constant::Integer(2)))))
+                    / tr(SyntaxComponent::Constant(Constant::Integer(1)))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_function_definitions() {
+        // fn twice(int n) -> int { return twice(n); }
+        let tokens = vec![
+            Token::Fn,
+            Token::Identifier("twice".into()),
+            Token::ParenthesisOpening,
+            Token::IntType,
+            Token::Identifier("n".into()),
+            Token::ParenthesisClosing,
+            Token::Arrow,
+            Token::IntType,
+            Token::CurlyOpening,
+            Token::Return,
+            Token::Identifier("twice".into()),
+            Token::ParenthesisOpening,
+            Token::Identifier("n".into()),
+            Token::ParenthesisClosing,
+            Token::Semicolon,
+            Token::CurlyClosing,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Function("twice".into()))
+                / (tr(SyntaxComponent::Parameters)
+                    / (tr(SyntaxComponent::Declaration)
+                        / tr(SyntaxComponent::Type(Type::Integer))
+                        / tr(SyntaxComponent::Identifier("n".into()))))
+                / tr(SyntaxComponent::Type(Type::Integer))
+                / (tr(SyntaxComponent::Sequence)
+                    / (tr(SyntaxComponent::Return)
+                        / (tr(SyntaxComponent::Call("twice".into()))
+                            / tr(SyntaxComponent::Identifier("n".into()))))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_functions_without_result() {
+        // fn greet() { return; } greet();
+        let tokens = vec![
+            Token::Fn,
+            Token::Identifier("greet".into()),
+            Token::ParenthesisOpening,
+            Token::ParenthesisClosing,
+            Token::CurlyOpening,
+            Token::Return,
+            Token::Semicolon,
+            Token::CurlyClosing,
+            Token::Identifier("greet".into()),
+            Token::ParenthesisOpening,
+            Token::ParenthesisClosing,
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Function("greet".into()))
+                / tr(SyntaxComponent::Parameters)
+                / tr(SyntaxComponent::Null)
+                / (tr(SyntaxComponent::Sequence) / tr(SyntaxComponent::Return)))
+            / tr(SyntaxComponent::Call("greet".into()));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_only_accepts_functions_at_the_top_level() {
+        let source = "{ fn inner() {} }\nfn outer() {}";
+        let (mut tokens, _) = crate::lexical_analysis::lexical_analysis(source.chars());
+        let (tree, diagnostics) = syntax_analysis(&mut tokens);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span.unwrap().line, 1);
+
+        let statements: Vec<&SyntaxComponent> =
+            tree.iter().map(|node| &node.data().component).collect();
+        assert_eq!(*statements[1], SyntaxComponent::Function("outer".into()));
+    }
+
+    #[test]
+    fn it_parses_array_declarations() {
+        // num[2] xs = [1, 2.5];
+        let tokens = vec![
+            Token::NumType,
+            Token::BracketOpening,
+            Token::IntegerConstant(2),
+            Token::BracketClosing,
+            Token::Identifier("xs".into()),
+            Token::OperatorAssignment,
+            Token::BracketOpening,
+            Token::IntegerConstant(1),
+            Token::Comma,
+            Token::Constant(2.5),
+            Token::BracketClosing,
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Declaration)
+                / tr(SyntaxComponent::Type(Type::Array(
+                    Box::new(Type::Number),
+                    2,
+                )))
+                / tr(SyntaxComponent::Identifier("xs".into()))
+                / (tr(SyntaxComponent::ArrayLiteral)
+                    / tr(SyntaxComponent::Constant(Constant::Integer(1)))
+                    / tr(SyntaxComponent::Constant(Constant::Float(2.5)))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_index_expressions() {
+        // xs[i + 1] = xs[i]++;
+        let tokens = vec![
+            Token::Identifier("xs".into()),
+            Token::BracketOpening,
+            Token::Identifier("i".into()),
+            Token::OperatorAddition,
+            Token::IntegerConstant(1),
+            Token::BracketClosing,
+            Token::OperatorAssignment,
+            Token::Identifier("xs".into()),
+            Token::BracketOpening,
+            Token::Identifier("i".into()),
+            Token::BracketClosing,
+            Token::OperatorIncrement,
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Assignment)
+                / (tr(SyntaxComponent::Index)
+                    / tr(SyntaxComponent::Identifier("xs".into()))
+                    / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Add))
+                        / tr(SyntaxComponent::Identifier("i".into()))
+                        / tr(SyntaxComponent::Constant(Constant::Integer(1)))))
+                / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::Increment))
+                    / (tr(SyntaxComponent::Index)
+                        / tr(SyntaxComponent::Identifier("xs".into()))
+                        / tr(SyntaxComponent::Identifier("i".into())))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_struct_definitions() {
+        // struct Point { num x; num y; } struct Point p;
+        let tokens = vec![
+            Token::Struct,
+            Token::Identifier("Point".into()),
+            Token::CurlyOpening,
+            Token::NumType,
+            Token::Identifier("x".into()),
+            Token::Semicolon,
+            Token::NumType,
+            Token::Identifier("y".into()),
+            Token::Semicolon,
+            Token::CurlyClosing,
+            Token::Struct,
+            Token::Identifier("Point".into()),
+            Token::Identifier("p".into()),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Struct("Point".into()))
+                / (tr(SyntaxComponent::Declaration)
+                    / tr(SyntaxComponent::Type(Type::Number))
+                    / tr(SyntaxComponent::Identifier("x".into())))
+                / (tr(SyntaxComponent::Declaration)
+                    / tr(SyntaxComponent::Type(Type::Number))
+                    / tr(SyntaxComponent::Identifier("y".into()))))
+            / (tr(SyntaxComponent::Declaration)
+                / tr(SyntaxComponent::Type(Type::Struct("Point".into())))
+                / tr(SyntaxComponent::Identifier("p".into())));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_member_access() {
+        // line.to.x = p.x;
+        let tokens = vec![
+            Token::Identifier("line".into()),
+            Token::Dot,
+            Token::Identifier("to".into()),
+            Token::Dot,
+            Token::Identifier("x".into()),
+            Token::OperatorAssignment,
+            Token::Identifier("p".into()),
+            Token::Dot,
+            Token::Identifier("x".into()),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Assignment)
+                / (tr(SyntaxComponent::Member("x".into()))
+                    / (tr(SyntaxComponent::Member("to".into()))
+                        / tr(SyntaxComponent::Identifier("line".into()))))
+                / (tr(SyntaxComponent::Member("x".into()))
+                    / tr(SyntaxComponent::Identifier("p".into()))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_only_accepts_structs_at_the_top_level() {
+        let source = "{ struct Inner { num x; } }\nstruct Outer { num x; }";
+        let (mut tokens, _) = crate::lexical_analysis::lexical_analysis(source.chars());
+        let (tree, diagnostics) = syntax_analysis(&mut tokens);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span.unwrap().line, 1);
+
+        let statements: Vec<&SyntaxComponent> =
+            tree.iter().map(|node| &node.data().component).collect();
+        assert_eq!(*statements[1], SyntaxComponent::Struct("Outer".into()));
+    }
+
+    #[test]
+    fn it_parses_enum_definitions() {
+        // enum Color { Red, Green, } enum Color c = Color.Red;
+        let tokens = vec![
+            Token::Enum,
+            Token::Identifier("Color".into()),
+            Token::CurlyOpening,
+            Token::Identifier("Red".into()),
+            Token::Comma,
+            Token::Identifier("Green".into()),
+            Token::Comma,
+            Token::CurlyClosing,
+            Token::Enum,
+            Token::Identifier("Color".into()),
+            Token::Identifier("c".into()),
+            Token::OperatorAssignment,
+            Token::Identifier("Color".into()),
+            Token::Dot,
+            Token::Identifier("Red".into()),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Enum("Color".into()))
+                / tr(SyntaxComponent::Identifier("Red".into()))
+                / tr(SyntaxComponent::Identifier("Green".into())))
+            / (tr(SyntaxComponent::Declaration)
+                / tr(SyntaxComponent::Type(Type::Enum("Color".into())))
+                / tr(SyntaxComponent::Identifier("c".into()))
+                / (tr(SyntaxComponent::Member("Red".into()))
+                    / tr(SyntaxComponent::Identifier("Color".into()))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_switch_statements() {
+        // switch (i) { case 1, 2: i++; break; default: }
+        let tokens = vec![
+            Token::Switch,
+            Token::ParenthesisOpening,
+            Token::Identifier("i".into()),
+            Token::ParenthesisClosing,
+            Token::CurlyOpening,
+            Token::Case,
+            Token::IntegerConstant(1),
+            Token::Comma,
+            Token::IntegerConstant(2),
+            Token::Colon,
+            Token::Identifier("i".into()),
+            Token::OperatorIncrement,
+            Token::Semicolon,
+            Token::Break,
+            Token::Semicolon,
+            Token::Default,
+            Token::Colon,
+            Token::CurlyClosing,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Switch)
+                / tr(SyntaxComponent::Identifier("i".into()))
+                / (tr(SyntaxComponent::Case)
+                    / tr(SyntaxComponent::Constant(Constant::Integer(1)))
+                    / tr(SyntaxComponent::Constant(Constant::Integer(2)))
+                    / (tr(SyntaxComponent::Sequence)
+                        / (tr(SyntaxComponent::UnaryOperation(UnaryOperation::Increment))
+                            / tr(SyntaxComponent::Identifier("i".into())))
+                        / tr(SyntaxComponent::Break)))
+                / (tr(SyntaxComponent::Default) / tr(SyntaxComponent::Sequence)));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_conditional_expressions() {
+        // x = a || b ? 1 : c ? 2 : 3;
+        let tokens = vec![
+            Token::Identifier("x".into()),
+            Token::OperatorAssignment,
+            Token::Identifier("a".into()),
+            Token::OperatorOr,
+            Token::Identifier("b".into()),
+            Token::Question,
+            Token::IntegerConstant(1),
+            Token::Colon,
+            Token::Identifier("c".into()),
+            Token::Question,
+            Token::IntegerConstant(2),
+            Token::Colon,
+            Token::IntegerConstant(3),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::Assignment)
+                / tr(SyntaxComponent::Identifier("x".into()))
+                / (tr(SyntaxComponent::Conditional)
+                    / (tr(SyntaxComponent::LogicalOperation(LogicalOperation::Or))
+                        / tr(SyntaxComponent::Identifier("a".into()))
+                        / tr(SyntaxComponent::Identifier("b".into())))
+                    / tr(SyntaxComponent::Constant(Constant::Integer(1)))
+                    / (tr(SyntaxComponent::Conditional)
+                        / tr(SyntaxComponent::Identifier("c".into()))
+                        / tr(SyntaxComponent::Constant(Constant::Integer(2)))
+                        / tr(SyntaxComponent::Constant(Constant::Integer(3))))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
+    #[test]
+    fn it_parses_constant_declarations() {
+        // const num N = 10 * 4;
+        let tokens = vec![
+            Token::Const,
+            Token::NumType,
+            Token::Identifier("N".into()),
+            Token::OperatorAssignment,
+            Token::IntegerConstant(10),
+            Token::OperatorMultiplication,
+            Token::IntegerConstant(4),
+            Token::Semicolon,
+            Token::EOF,
+        ];
+
+        let expected = tr(SyntaxComponent::Sequence)
+            / (tr(SyntaxComponent::ConstantDeclaration)
+                / tr(SyntaxComponent::Type(Type::Number))
+                / tr(SyntaxComponent::Identifier("N".into()))
+                / (tr(SyntaxComponent::BinaryOperation(BinaryOperation::Multiply))
+                    / tr(SyntaxComponent::Constant(Constant::Integer(10)))
+                    / tr(SyntaxComponent::Constant(Constant::Integer(4)))));
+
+        assert_tokens_parse_to(tokens, expected);
+    }
+
     #[test]
     fn it_parses_negation() {
         // bool foo = !bar;
@@ -236,4 +987,83 @@ mod tests {
 
         assert_tokens_parse_to(tokens, expected);
     }
+
+    #[test]
+    fn it_attaches_spans_to_nodes() {
+        let source = "num foo = 1;\nfoo = foo + 2;";
+        let (mut tokens, _) = crate::lexical_analysis::lexical_analysis(source.chars());
+        let (tree, _) = syntax_analysis(&mut tokens);
+
+        let mut statements = tree.iter();
+        let declaration = statements.next().unwrap();
+        assert_eq!(declaration.data().span, Span::new(0, 12, 1, 1));
+
+        let assignment = statements.next().unwrap();
+        assert_eq!(assignment.data().span, Span::new(13, 27, 2, 1));
+
+        let addition = assignment.iter().nth(1).unwrap();
+        assert_eq!(addition.data().span, Span::new(19, 26, 2, 7));
+    }
+
+    #[test]
+    fn it_reports_every_syntax_error() {
+        let source = "num x = 1;\nx = ;\nif (x > 1 {\n  x = 2;\n}\nbool = true;\nx++;";
+        let (mut tokens, _) = crate::lexical_analysis::lexical_analysis(source.chars());
+        let (tree, diagnostics) = syntax_analysis(&mut tokens);
+
+        let lines: Vec<usize> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.span.unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![2, 3, 6]);
+
+        // statements after the broken ones are still parsed
+        let statements: Vec<&SyntaxComponent> =
+            tree.iter().map(|node| &node.data().component).collect();
+        assert_eq!(statements.len(), 6);
+        assert!(matches!(statements[1], SyntaxComponent::Error(_)));
+        assert!(matches!(statements[2], SyntaxComponent::Error(_)));
+        assert_eq!(*statements[3], SyntaxComponent::Sequence);
+        assert!(matches!(statements[4], SyntaxComponent::Error(_)));
+        assert_eq!(
+            *statements[5],
+            SyntaxComponent::UnaryOperation(UnaryOperation::Increment)
+        );
+    }
+
+    #[test]
+    fn it_recovers_inside_blocks() {
+        let source = "{ foo = ; bar = 1; }";
+        let (mut tokens, _) = crate::lexical_analysis::lexical_analysis(source.chars());
+        let (tree, diagnostics) = syntax_analysis(&mut tokens);
+
+        assert_eq!(diagnostics.len(), 1);
+
+        let block = tree.iter().next().unwrap();
+        let statements: Vec<&SyntaxComponent> =
+            block.iter().map(|node| &node.data().component).collect();
+        assert!(matches!(statements[0], SyntaxComponent::Error(_)));
+        assert_eq!(*statements[1], SyntaxComponent::Assignment);
+    }
+
+    #[test]
+    fn it_keeps_the_name_of_broken_declarations() {
+        let source = "num x = 1 +;\nnum[K] xs;\nconst int N = ;\nx = 1;";
+        let (mut tokens, _) = crate::lexical_analysis::lexical_analysis(source.chars());
+        let (tree, diagnostics) = syntax_analysis(&mut tokens);
+
+        assert_eq!(diagnostics.len(), 3);
+
+        let names: Vec<Option<&SyntaxComponent>> = tree
+            .iter()
+            .map(|node| node.iter().next().map(|child| &child.data().component))
+            .collect();
+        assert_eq!(names[0], Some(&SyntaxComponent::Identifier("x".into())));
+        assert_eq!(names[1], Some(&SyntaxComponent::Identifier("xs".into())));
+        assert_eq!(names[2], Some(&SyntaxComponent::Identifier("N".into())));
+        assert_eq!(
+            tree.iter().nth(3).unwrap().data().component,
+            SyntaxComponent::Assignment
+        );
+    }
 }
@@ -1,17 +1,51 @@
 use regex::Regex;
-use std::fmt;
+use std::{fmt, iter::Peekable};
 
+use crate::{
+    diagnostic::{Diagnostic, ErrorCode, Phase},
+    span::Span,
+};
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Debug, PartialEq)]
 pub enum Token {
     If,
+    Else,
     Semicolon,
+    Comma,
+    Arrow,
     For,
+    While,
+    Do,
+    Break,
+    Continue,
+    Print,
+    Read,
+    Fn,
+    Return,
+    Const,
+    Struct,
+    Enum,
+    Switch,
+    Case,
+    Default,
+    Colon,
+    // conditional expression, as in c ? a : b
+    Question,
+    // member access, as in p.x
+    Dot,
     ParenthesisOpening,
     ParenthesisClosing,
     OperatorMultiplication,
     OperatorMultiplyBy,
     OperatorDivision,
     OperatorDivideBy,
+    OperatorIntegerDivision,
+    OperatorIntegerDivideBy,
+    OperatorModulo,
+    OperatorModuloBy,
+    OperatorPower,
+    OperatorPowerBy,
     OperatorAddition,
     OperatorSubtraction,
     OperatorIncrement,
@@ -25,15 +59,38 @@ pub enum Token {
     OperatorGreaterThanOrEqual,
     OperatorEqual,
     OperatorNotEqual,
-    Constant(f32),
+    OperatorAnd,
+    OperatorOr,
+    OperatorBitwiseAnd,
+    OperatorBitwiseAndBy,
+    OperatorBitwiseOr,
+    OperatorBitwiseOrBy,
+    OperatorBitwiseXor,
+    OperatorBitwiseXorBy,
+    OperatorBitwiseNot,
+    OperatorShiftLeft,
+    OperatorShiftLeftBy,
+    OperatorShiftRight,
+    OperatorShiftRightBy,
+    Constant(f64),
+    StringLiteral(String),
+    CharacterLiteral(char),
+    IntegerConstant(i64),
     Identifier(String),
     CurlyOpening,
     CurlyClosing,
+    BracketOpening,
+    BracketClosing,
     True,
     False,
     Not,
     NumType,
+    IntType,
+    StringType,
+    CharType,
     BoolType,
+    // text that could not be tokenized, already reported by the lexical analysis
+    Error(String),
     EOF,
 }
 
@@ -44,8 +101,28 @@ impl std::fmt::Display for Token {
             "{}",
             match self {
                 Token::If => "IF".to_string(),
+                Token::Else => "ELSE".to_string(),
                 Token::Semicolon => ";".to_string(),
+                Token::Comma => ",".to_string(),
+                Token::Arrow => "->".to_string(),
                 Token::For => "FOR".to_string(),
+                Token::While => "WHILE".to_string(),
+                Token::Do => "DO".to_string(),
+                Token::Break => "BREAK".to_string(),
+                Token::Continue => "CONTINUE".to_string(),
+                Token::Print => "PRINT".to_string(),
+                Token::Read => "READ".to_string(),
+                Token::Fn => "FN".to_string(),
+                Token::Const => "CONST".to_string(),
+                Token::Return => "RETURN".to_string(),
+                Token::Struct => "STRUCT".to_string(),
+                Token::Enum => "ENUM".to_string(),
+                Token::Switch => "SWITCH".to_string(),
+                Token::Case => "CASE".to_string(),
+                Token::Default => "DEFAULT".to_string(),
+                Token::Colon => ":".to_string(),
+                Token::Question => "?".to_string(),
+                Token::Dot => ".".to_string(),
                 Token::ParenthesisOpening => "(".to_string(),
                 Token::ParenthesisClosing => ")".to_string(),
                 Token::OperatorAssignment => "=".to_string(),
@@ -53,6 +130,12 @@ impl std::fmt::Display for Token {
                 Token::OperatorMultiplyBy => "*=".to_string(),
                 Token::OperatorDivision => "/".to_string(),
                 Token::OperatorDivideBy => "/=".to_string(),
+                Token::OperatorIntegerDivision => "\\".to_string(),
+                Token::OperatorIntegerDivideBy => "\\=".to_string(),
+                Token::OperatorModulo => "%".to_string(),
+                Token::OperatorModuloBy => "%=".to_string(),
+                Token::OperatorPower => "**".to_string(),
+                Token::OperatorPowerBy => "**=".to_string(),
                 Token::OperatorSubtraction => "-".to_string(),
                 Token::OperatorAddition => "+".to_string(),
                 Token::OperatorIncrement => "++".to_string(),
@@ -65,55 +148,91 @@ impl std::fmt::Display for Token {
                 Token::OperatorGreaterThanOrEqual => "GTE".to_string(),
                 Token::OperatorEqual => "EQ".to_string(),
                 Token::OperatorNotEqual => "NE".to_string(),
+                Token::OperatorAnd => "AND".to_string(),
+                Token::OperatorOr => "OR".to_string(),
+                Token::OperatorBitwiseAnd => "&".to_string(),
+                Token::OperatorBitwiseAndBy => "&=".to_string(),
+                Token::OperatorBitwiseOr => "|".to_string(),
+                Token::OperatorBitwiseOrBy => "|=".to_string(),
+                Token::OperatorBitwiseXor => "^".to_string(),
+                Token::OperatorBitwiseXorBy => "^=".to_string(),
+                Token::OperatorBitwiseNot => "~".to_string(),
+                Token::OperatorShiftLeft => "<<".to_string(),
+                Token::OperatorShiftLeftBy => "<<=".to_string(),
+                Token::OperatorShiftRight => ">>".to_string(),
+                Token::OperatorShiftRightBy => ">>=".to_string(),
                 Token::CurlyOpening => "{".to_string(),
                 Token::CurlyClosing => "}".to_string(),
+                Token::BracketOpening => "[".to_string(),
+                Token::BracketClosing => "]".to_string(),
                 Token::Constant(value) => format!("Constant({value})"),
+                Token::IntegerConstant(value) => format!("IntegerConstant({value})"),
+                Token::StringLiteral(value) => format!("StringLiteral({value:?})"),
+                Token::CharacterLiteral(value) => format!("CharacterLiteral({value:?})"),
                 Token::Identifier(name) => format!("Identifier({name})"),
                 Token::True => "true".to_string(),
                 Token::False => "false".to_string(),
                 Token::NumType => "num".to_string(),
+                Token::IntType => "int".to_string(),
+                Token::StringType => "str".to_string(),
+                Token::CharType => "char".to_string(),
                 Token::BoolType => "bool".to_string(),
                 Token::Not => "NOT".to_string(),
+                Token::Error(text) => format!("Error({text})"),
                 Token::EOF => "EOF".to_string(),
             }
         )
     }
 }
 
-impl Token {
-    pub fn is_binary_operator(self: &Self) -> bool {
-        match self {
-            Token::OperatorMultiplication
-            | Token::OperatorDivision
-            | Token::OperatorAddition
-            | Token::OperatorSubtraction
-            | Token::OperatorIncreaseBy
-            | Token::OperatorDecreaseBy
-            | Token::OperatorAssignment
-            | Token::OperatorLessThan
-            | Token::OperatorLessThanOrEqual
-            | Token::OperatorGreaterThan
-            | Token::OperatorGreaterThanOrEqual
-            | Token::OperatorEqual
-            | Token::OperatorNotEqual => true,
-            Token::OperatorIncrement | Token::OperatorDecrement | Token::Not => false,
-            _ => panic!("Token is not operator: {}", self),
-        }
+/// A token along with the location it was read from
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+impl SpannedToken {
+    pub fn new(token: Token, span: Span) -> Self {
+        Self { token, span }
     }
 }
 
 impl TryFrom<String> for Token {
     type Error = String;
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
+    fn try_from(value: String) -> Result<Self, String> {
         let ok_value = match value.as_str() {
             "for" => Token::For,
+            "while" => Token::While,
+            "do" => Token::Do,
+            "break" => Token::Break,
+            "continue" => Token::Continue,
+            "print" => Token::Print,
+            "read" => Token::Read,
+            "fn" => Token::Fn,
+            "const" => Token::Const,
+            "return" => Token::Return,
+            "struct" => Token::Struct,
+            "enum" => Token::Enum,
+            "switch" => Token::Switch,
+            "case" => Token::Case,
+            "default" => Token::Default,
             "if" => Token::If,
+            "else" => Token::Else,
             "true" => Token::True,
             "false" => Token::False,
             "num" => Token::NumType,
+            "int" => Token::IntType,
+            "str" => Token::StringType,
+            "char" => Token::CharType,
             "bool" => Token::BoolType,
             ";" => Token::Semicolon,
+            "," => Token::Comma,
+            "." => Token::Dot,
+            ":" => Token::Colon,
+            "?" => Token::Question,
+            "->" => Token::Arrow,
             "!" => Token::Not,
             "(" => Token::ParenthesisOpening,
             ")" => Token::ParenthesisClosing,
@@ -121,6 +240,12 @@ impl TryFrom<String> for Token {
             "*=" => Token::OperatorMultiplyBy,
             "/" => Token::OperatorDivision,
             "/=" => Token::OperatorDivideBy,
+            "\\" => Token::OperatorIntegerDivision,
+            "\\=" => Token::OperatorIntegerDivideBy,
+            "%" => Token::OperatorModulo,
+            "%=" => Token::OperatorModuloBy,
+            "**" => Token::OperatorPower,
+            "**=" => Token::OperatorPowerBy,
             "-" => Token::OperatorSubtraction,
             "--" => Token::OperatorDecrement,
             "-=" => Token::OperatorDecreaseBy,
@@ -133,12 +258,33 @@ impl TryFrom<String> for Token {
             "<=" => Token::OperatorLessThanOrEqual,
             "==" => Token::OperatorEqual,
             "!=" => Token::OperatorNotEqual,
+            "&&" => Token::OperatorAnd,
+            "||" => Token::OperatorOr,
+            "&" => Token::OperatorBitwiseAnd,
+            "&=" => Token::OperatorBitwiseAndBy,
+            "|" => Token::OperatorBitwiseOr,
+            "|=" => Token::OperatorBitwiseOrBy,
+            "^" => Token::OperatorBitwiseXor,
+            "^=" => Token::OperatorBitwiseXorBy,
+            "~" => Token::OperatorBitwiseNot,
+            "<<" => Token::OperatorShiftLeft,
+            "<<=" => Token::OperatorShiftLeftBy,
+            ">>" => Token::OperatorShiftRight,
+            ">>=" => Token::OperatorShiftRightBy,
             "=" => Token::OperatorAssignment,
             "{" => Token::CurlyOpening,
             "}" => Token::CurlyClosing,
+            "[" => Token::BracketOpening,
+            "]" => Token::BracketClosing,
             _ => {
-                if Regex::new(r"^[0-9]+(\.[0-9]+)?$").unwrap().is_match(&value) {
-                    Token::Constant(value.parse().unwrap())
+                if value.starts_with('"') {
+                    Token::StringLiteral(parse_quoted_literal(&value, '"', "string")?)
+                } else if value.starts_with('\'') {
+                    Token::CharacterLiteral(parse_character_literal(&value)?)
+                } else if value
+                    .starts_with(|character: char| character.is_ascii_digit() || character == '.')
+                {
+                    parse_numeric_literal(&value)?
                 } else if Regex::new(r"^[a-zA-Z_][a-zA-Z_0-9]*$")
                     .unwrap()
                     .is_match(&value)
@@ -154,24 +300,178 @@ impl TryFrom<String> for Token {
     }
 }
 
-pub fn expect_token(
-    mut input: impl Iterator<Item = Token>,
-    expected_token: Token,
-) -> Result<Token, String> {
-    match input.next() {
-        Some(next_token) => {
-            if next_token != expected_token {
-                Err(format!(
-                    "Unexpected token {}, expected {}",
-                    next_token, expected_token
-                ))
-            } else {
-                Ok(next_token)
+/**
+ * Numeric literals are decimal integers, hexadecimal (`0x1F`) or binary
+ * (`0b1010`) integers, or decimal floats with an optional exponent (`1.5e-3`,
+ * `.5`, `2e10`). Underscores can separate digits: `1_000_000`.
+ */
+fn parse_numeric_literal(value: &str) -> Result<Token, String> {
+    // underscores are only allowed between two digits
+    let integer = Regex::new(r"^[0-9](_?[0-9])*$").unwrap();
+    let hexadecimal = Regex::new(r"^0[xX][0-9a-fA-F](_?[0-9a-fA-F])*$").unwrap();
+    let binary = Regex::new(r"^0[bB][01](_?[01])*$").unwrap();
+    let float =
+        Regex::new(r"^([0-9](_?[0-9])*)?(\.[0-9](_?[0-9])*)?([eE][+-]?[0-9](_?[0-9])*)?$").unwrap();
+
+    let digits = value.replace('_', "");
+    let too_large = |_| format!("Integer literal '{value}' is too large");
+
+    if integer.is_match(value) {
+        Ok(Token::IntegerConstant(digits.parse().map_err(too_large)?))
+    } else if hexadecimal.is_match(value) {
+        Ok(Token::IntegerConstant(
+            i64::from_str_radix(&digits[2..], 16).map_err(too_large)?,
+        ))
+    } else if binary.is_match(value) {
+        Ok(Token::IntegerConstant(
+            i64::from_str_radix(&digits[2..], 2).map_err(too_large)?,
+        ))
+    } else if float.is_match(value) {
+        Ok(Token::Constant(digits.parse().unwrap()))
+    } else if value.len() == 2 && Regex::new(r"^0[xXbB]$").unwrap().is_match(value) {
+        Err(format!("Missing digits after '{value}'"))
+    } else if Regex::new(r"^0[xX]").unwrap().is_match(value) {
+        Err(format!("Invalid digits in hexadecimal literal '{value}'"))
+    } else if Regex::new(r"^0[bB]").unwrap().is_match(value) {
+        Err(format!("Invalid digits in binary literal '{value}'"))
+    } else if value.ends_with('.') {
+        Err(format!(
+            "Missing digits after the decimal point in '{value}'"
+        ))
+    } else if value.ends_with(['e', 'E', '+', '-']) {
+        Err(format!("Missing exponent digits in '{value}'"))
+    } else if value.contains('_') {
+        Err(format!(
+            "Digit separators must be placed between digits in '{value}'"
+        ))
+    } else {
+        Err(format!("Malformed numeric literal '{value}'"))
+    }
+}
+
+/**
+ * String literals are enclosed in double quotes and character literals in
+ * single quotes. Neither can span multiple lines. Supported escape sequences
+ * are `\n`, `\t`, `\"`, `\'`, `\\` and `\u{...}`, which takes the
+ * hexadecimal code of any unicode character.
+ */
+fn parse_quoted_literal(value: &str, quote: char, kind: &str) -> Result<String, String> {
+    let unterminated =
+        || format!("Unterminated {kind} literal, {kind}s end with {quote} on the same line");
+    let mut characters = value.chars().skip(1);
+    let mut result = String::new();
+
+    loop {
+        let character = characters.next().ok_or_else(unterminated)?;
+
+        match character {
+            _ if character == quote => break,
+            '\\' => {
+                let escaped = match characters.next() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('"') => '"',
+                    Some('\'') => '\'',
+                    Some('\\') => '\\',
+                    Some('u') => parse_unicode_escape(&mut characters)?,
+                    Some(other) => {
+                        return Err(format!(
+                            "Unknown escape sequence '\\{other}' in {kind} literal {value}"
+                        ))
+                    }
+                    None => return Err(unterminated()),
+                };
+                result.push(escaped);
             }
+            _ => result.push(character),
         }
-        None => Err(format!(
-            "Unexpected end of token stream, expected {}",
-            expected_token
+    }
+
+    Ok(result)
+}
+
+fn parse_character_literal(value: &str) -> Result<char, String> {
+    let contents = parse_quoted_literal(value, '\'', "character")?;
+    let mut characters = contents.chars();
+
+    match (characters.next(), characters.next()) {
+        (Some(character), None) => Ok(character),
+        _ => Err(format!(
+            "Character literals must contain exactly one character, got {value}"
+        )),
+    }
+}
+
+/// Reads the `{...}` part of a `\u{...}` escape sequence
+fn parse_unicode_escape(characters: &mut impl Iterator<Item = char>) -> Result<char, String> {
+    if characters.next() != Some('{') {
+        return Err(String::from("Expected '{' after '\\u' in escape sequence"));
+    }
+
+    let code: String = characters
+        .by_ref()
+        .take_while(|character| *character != '}')
+        .collect();
+
+    if code.is_empty() || code.len() > 6 {
+        return Err(format!(
+            "Unicode escape sequences need 1 to 6 hexadecimal digits, got '{code}'"
+        ));
+    }
+
+    u32::from_str_radix(&code, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or(format!("'{code}' is not a valid unicode character code"))
+}
+
+/**
+ * Error for a parser running into an error token.
+ *
+ * It belongs to the lexical analysis, which already reported it; the parser
+ * only uses it to recover and does not report it again.
+ */
+pub fn invalid_token_error(token: &SpannedToken) -> Diagnostic {
+    Diagnostic::error(
+        Phase::Lexical,
+        ErrorCode::InvalidToken,
+        format!("Invalid token {}", token.token),
+    )
+    .with_span(token.span)
+}
+
+/// Consumes the next token if it is `expected_token`. Other tokens are left in the stream
+pub fn expect_token(
+    input: &mut Peekable<impl Iterator<Item = SpannedToken>>,
+    expected_token: Token,
+) -> Result<SpannedToken, Diagnostic> {
+    if let Some(next_token) = input.next_if(|next_token| next_token.token == expected_token) {
+        return Ok(next_token);
+    }
+
+    match input.peek() {
+        Some(
+            next_token @ SpannedToken {
+                token: Token::Error(_),
+                ..
+            },
+        ) => Err(invalid_token_error(next_token)),
+        Some(next_token) => Err(Diagnostic::error(
+            Phase::Syntax,
+            ErrorCode::UnexpectedToken,
+            format!(
+                "Unexpected token {}, expected {}",
+                next_token.token, expected_token
+            ),
+        )
+        .with_span(next_token.span)),
+        None => Err(Diagnostic::error(
+            Phase::Syntax,
+            ErrorCode::UnexpectedEndOfInput,
+            format!(
+                "Unexpected end of token stream, expected {}",
+                expected_token
+            ),
         )),
     }
 }